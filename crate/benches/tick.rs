@@ -0,0 +1,38 @@
+//! Full-tick timings for each `Testbed` at a few world sizes. Run with
+//! `cargo bench`; add `--features parallel` to time the threaded pass.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use sand_sim::{Testbed, World};
+
+/// Small, the web frontend's default, and a large desktop canvas.
+const SIZES: [(usize, usize); 3] = [(150, 100), (300, 200), (600, 400)];
+
+/// Ticks run before timing starts, so sand has settled, the waterfall is
+/// flowing and the fire has taken hold.
+const WARMUP_TICKS: usize = 30;
+
+fn warmed_up(bed: Testbed, width: usize, height: usize) -> World {
+    let mut world = bed.build(width, height);
+    for _ in 0..WARMUP_TICKS { world.tick(); }
+    world
+}
+
+fn tick(c: &mut Criterion) {
+    for bed in Testbed::ALL {
+        let mut group = c.benchmark_group(bed.name());
+        group.sample_size(20);
+        for (width, height) in SIZES {
+            group.throughput(Throughput::Elements((width * height) as u64));
+            let id = BenchmarkId::from_parameter(format!("{width}x{height}"));
+            // A fresh world per sample: the fire would burn out over a long
+            // run of ticks on one world.
+            group.bench_function(id, |b| {
+                b.iter_batched_ref(|| warmed_up(bed, width, height), |world| world.tick(), BatchSize::LargeInput)
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, tick);
+criterion_main!(benches);
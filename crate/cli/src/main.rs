@@ -0,0 +1,192 @@
+//! Headless sand-sim runner, for long regression runs in CI or on servers.
+//!
+//! Loads a scene, runs it for a number of ticks without rendering, then
+//! prints the ticks/second and how many cells of each species are left.
+//! Scenes are `.rle` files as written by `World::to_rle`, or, when built
+//! with the `image` feature, `.png` stencils read with the default palette.
+//! With `--snapshot-every`, the world is also saved as an `.rle` file at a
+//! fixed tick interval.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use sand_sim::{species_name, World};
+
+const USAGE: &str = "\
+usage: sand-cli <scene.rle|scene.png> [options]
+
+options:
+  --ticks <n>               ticks to run (default 1000)
+  --snapshot-every <n>      save the world every n ticks
+  --snapshot-dir <dir>      where snapshots go (default: current directory)
+  --size <w>x<h>            world size for PNG scenes (default 300x200)";
+
+#[derive(Debug)]
+struct Options {
+    scene: PathBuf,
+    ticks: u64,
+    snapshot_every: Option<u64>,
+    snapshot_dir: PathBuf,
+    size: (usize, usize),
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut scene = None;
+    let mut opts = Options {
+        scene: PathBuf::new(),
+        ticks: 1000,
+        snapshot_every: None,
+        snapshot_dir: PathBuf::from("."),
+        size: (300, 200),
+    };
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--ticks" => opts.ticks = parse_count(value()?)?,
+            "--snapshot-every" => opts.snapshot_every = Some(parse_count(value()?)?).filter(|&n| n > 0),
+            "--snapshot-dir" => opts.snapshot_dir = PathBuf::from(value()?),
+            "--size" => {
+                let v = value()?;
+                let (w, h) = v.split_once('x').ok_or_else(|| format!("bad size `{v}`"))?;
+                opts.size = (parse_count(w)? as usize, parse_count(h)? as usize);
+            }
+            other if other.starts_with("--") => return Err(format!("unknown option `{other}`")),
+            path if scene.is_none() => scene = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument `{extra}`")),
+        }
+    }
+    opts.scene = scene.ok_or("missing scene file")?;
+    Ok(opts)
+}
+
+fn parse_count(v: &str) -> Result<u64, String> {
+    v.parse().map_err(|_| format!("bad number `{v}`"))
+}
+
+fn load_scene(path: &Path, size: (usize, usize)) -> Result<World, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let is_png = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    if !is_png {
+        return World::from_rle(&bytes).map_err(|e| format!("{}: {e}", path.display()));
+    }
+    #[cfg(feature = "image")]
+    {
+        let mut world = World::new(size.0, size.1);
+        world.load_from_image(&bytes, &sand_sim::ColorMap::from_palette())?;
+        Ok(world)
+    }
+    #[cfg(not(feature = "image"))]
+    {
+        let _ = size;
+        Err("PNG scenes need sand-cli built with `--features image`".into())
+    }
+}
+
+/// Every species present other than empty, with its cell count, in ID order.
+fn species_counts(world: &World) -> Vec<(String, u32)> {
+    world
+        .stats()
+        .counts()
+        .into_iter()
+        .enumerate()
+        .skip(1)
+        .filter(|&(_, n)| n > 0)
+        .map(|(id, n)| (species_name(id as u8), n))
+        .collect()
+}
+
+fn save_snapshot(world: &World, dir: &Path, tick: u64) -> Result<(), String> {
+    let path = dir.join(format!("tick-{tick:08}.rle"));
+    std::fs::write(&path, world.to_rle()).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn run(opts: &Options) -> Result<(), String> {
+    let mut world = load_scene(&opts.scene, opts.size)?;
+    println!("{} ({}x{})", opts.scene.display(), world.width(), world.height());
+    if opts.snapshot_every.is_some() {
+        std::fs::create_dir_all(&opts.snapshot_dir)
+            .map_err(|e| format!("{}: {e}", opts.snapshot_dir.display()))?;
+    }
+
+    let start = Instant::now();
+    for tick in 1..=opts.ticks {
+        world.tick();
+        if opts.snapshot_every.is_some_and(|n| tick % n == 0) {
+            save_snapshot(&world, &opts.snapshot_dir, tick)?;
+        }
+    }
+    let secs = start.elapsed().as_secs_f64();
+    println!("{} ticks in {secs:.2}s ({:.0} ticks/s)", opts.ticks, opts.ticks as f64 / secs.max(1e-9));
+    for (name, count) in species_counts(&world) {
+        println!("{name:<12} {count:>10}");
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let opts = match parse_args(&args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&opts) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_scene_and_options() {
+        let opts = parse_args(&args("scene.rle --ticks 50 --snapshot-every 10 --size 64x32")).unwrap();
+        assert_eq!(opts.scene, PathBuf::from("scene.rle"));
+        assert_eq!((opts.ticks, opts.snapshot_every, opts.size), (50, Some(10), (64, 32)));
+        assert_eq!(parse_args(&args("a.rle --snapshot-every 0")).unwrap().snapshot_every, None);
+        assert!(parse_args(&args("--ticks 5")).is_err(), "No scene");
+        assert!(parse_args(&args("a.rle b.rle")).is_err());
+        assert!(parse_args(&args("a.rle --ticks")).is_err());
+        assert!(parse_args(&args("a.rle --size 64")).is_err());
+        assert!(parse_args(&args("a.rle --speed 2")).is_err());
+    }
+
+    #[test]
+    fn runs_a_scene_and_writes_snapshots() {
+        let dir = std::env::temp_dir().join(format!("sand-cli-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut world = World::new(20, 20);
+        for x in 5..15 { world.set_cell(x, 2, 1); }
+        let scene = dir.join("scene.rle");
+        std::fs::write(&scene, world.to_rle()).unwrap();
+
+        let opts = Options {
+            scene,
+            ticks: 30,
+            snapshot_every: Some(10),
+            snapshot_dir: dir.join("snaps"),
+            size: (300, 200),
+        };
+        run(&opts).unwrap();
+        let snaps = std::fs::read_dir(dir.join("snaps")).unwrap().count();
+        let last = std::fs::read(dir.join("snaps/tick-00000030.rle")).unwrap();
+        let last = World::from_rle(&last).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(snaps, 3);
+        assert_eq!(species_counts(&last), vec![("sand".to_string(), 10)]);
+    }
+}
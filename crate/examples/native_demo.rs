@@ -0,0 +1,122 @@
+//! Windowed sand-sim sandbox for the desktop, no web toolchain needed.
+//!
+//!     cargo run --release --example native_demo --features native-demo
+//!
+//! Left mouse paints the selected species, right mouse erases. Number keys
+//! pick a species (shown in the title bar), the mouse wheel resizes the
+//! brush, Space pauses, `.` steps one tick while paused, C clears the world
+//! and Escape quits.
+
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Scale, Window, WindowOptions};
+use sand_sim::World;
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 200;
+const FRAME_RATE: usize = 60;
+const MAX_BRUSH: usize = 20;
+
+/// Species on the number keys, by simulation ID.
+const PALETTE: [(Key, u8, &str); 10] = [
+    (Key::Key1, 1, "sand"),
+    (Key::Key2, 2, "water"),
+    (Key::Key3, 3, "oil"),
+    (Key::Key4, 4, "wall"),
+    (Key::Key5, 5, "fire"),
+    (Key::Key6, 6, "plant"),
+    (Key::Key7, 8, "lava"),
+    (Key::Key8, 9, "stone"),
+    (Key::Key9, 12, "acid"),
+    (Key::Key0, 13, "wood"),
+];
+
+struct Demo {
+    world: World,
+    rgba: Vec<u8>,
+    pixels: Vec<u32>,
+    selected: usize,
+    brush: usize,
+    paused: bool,
+}
+
+impl Demo {
+    fn new() -> Demo {
+        Demo {
+            world: World::new(WIDTH, HEIGHT),
+            rgba: vec![0; WIDTH * HEIGHT * 4],
+            pixels: vec![0; WIDTH * HEIGHT],
+            selected: 0,
+            brush: 3,
+            paused: false,
+        }
+    }
+
+    fn title(&self) -> String {
+        let (_, _, name) = PALETTE[self.selected];
+        let state = if self.paused { " [paused]" } else { "" };
+        format!("sand-sim: {name}, brush {}{state}", self.brush)
+    }
+
+    /// Fills a disc of the brush radius around (cx, cy) with `species`.
+    fn paint(&mut self, cx: usize, cy: usize, species: u8) {
+        let r = self.brush as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy > r * r { continue; }
+                let (x, y) = (cx as isize + dx, cy as isize + dy);
+                if x < 0 || y < 0 { continue; }
+                self.world.set_cell(x as usize, y as usize, species);
+            }
+        }
+    }
+
+    /// The renderer's RGBA bytes repacked as minifb's `0RGB` words.
+    fn draw(&mut self) {
+        self.world.render_rgba(&mut self.rgba);
+        for (px, c) in self.pixels.iter_mut().zip(self.rgba.chunks_exact(4)) {
+            *px = u32::from_be_bytes([0, c[0], c[1], c[2]]);
+        }
+    }
+}
+
+fn main() {
+    let options = WindowOptions { scale: Scale::X4, ..WindowOptions::default() };
+    let mut window = match Window::new("sand-sim", WIDTH, HEIGHT, options) {
+        Ok(window) => window,
+        Err(e) => {
+            eprintln!("error: can't open a window: {e}");
+            std::process::exit(1);
+        }
+    };
+    window.set_target_fps(FRAME_RATE);
+
+    let mut demo = Demo::new();
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        for (i, &(key, _, _)) in PALETTE.iter().enumerate() {
+            if window.is_key_pressed(key, KeyRepeat::No) { demo.selected = i; }
+        }
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) { demo.paused = !demo.paused; }
+        if window.is_key_pressed(Key::C, KeyRepeat::No) { demo.world.clear(); }
+        if let Some((_, scroll)) = window.get_scroll_wheel() {
+            demo.brush = (demo.brush as f32 + scroll.signum()).clamp(0.0, MAX_BRUSH as f32) as usize;
+        }
+
+        if let Some((mx, my)) = window.get_mouse_pos(MouseMode::Discard) {
+            let (x, y) = (mx as usize, my as usize);
+            if window.get_mouse_down(MouseButton::Left) {
+                demo.paint(x, y, PALETTE[demo.selected].1);
+            } else if window.get_mouse_down(MouseButton::Right) {
+                demo.paint(x, y, 0);
+            }
+        }
+
+        let step = window.is_key_pressed(Key::Period, KeyRepeat::Yes);
+        if !demo.paused || step { demo.world.tick(); }
+
+        demo.draw();
+        window.set_title(&demo.title());
+        if let Err(e) = window.update_with_buffer(&demo.pixels, WIDTH, HEIGHT) {
+            eprintln!("error: {e}");
+            break;
+        }
+    }
+}
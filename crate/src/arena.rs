@@ -0,0 +1,135 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{reseed_rng, rng_state, xorshift, World};
+
+/// Seed a new arena's streams are drawn from, as `World::new` seeds the
+/// native RNG.
+const DEFAULT_SEED: u32 = 0xDEAD_BEEF;
+
+/// A batch of independent worlds stepped together, for headless runs over
+/// many small grids where calling `tick` on each in turn costs more than
+/// the ticks themselves.
+///
+/// Every `tick_all` draws one seed from the arena and hands each world its
+/// own stream derived from that seed and the world's index, so a run is
+/// reproducible from the arena's seed alone. With the `parallel` feature
+/// the worlds tick side by side on rayon's global pool, and because each
+/// one reseeds its stream first, results don't depend on the thread count
+/// or which thread ran which world.
+pub struct WorldArena {
+    worlds: Vec<World>,
+    seed: u32,
+}
+
+impl Default for WorldArena {
+    fn default() -> Self { WorldArena::with_seed(DEFAULT_SEED) }
+}
+
+impl WorldArena {
+    pub fn new() -> WorldArena { WorldArena::default() }
+
+    /// An empty arena whose ticks are drawn from `seed`.
+    pub fn with_seed(seed: u32) -> WorldArena { WorldArena { worlds: Vec::new(), seed: seed | 1 } }
+
+    /// Adds a world, returning its index.
+    pub fn push(&mut self, world: World) -> usize {
+        self.worlds.push(world);
+        self.worlds.len() - 1
+    }
+
+    /// Takes out the world at `index`, shifting later worlds down one.
+    /// Panics if `index` is out of range, like `Vec::remove`.
+    pub fn remove(&mut self, index: usize) -> World { self.worlds.remove(index) }
+
+    pub fn len(&self) -> usize { self.worlds.len() }
+    pub fn is_empty(&self) -> bool { self.worlds.is_empty() }
+
+    pub fn get(&self, index: usize) -> Option<&World> { self.worlds.get(index) }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut World> { self.worlds.get_mut(index) }
+
+    pub fn worlds(&self) -> &[World] { &self.worlds }
+    pub fn worlds_mut(&mut self) -> &mut [World] { &mut self.worlds }
+
+    /// Advances every world one frame, as `World::tick` would.
+    pub fn tick_all(&mut self) { self.tick_all_n(1); }
+
+    /// Advances every world `n` frames, as `World::tick_n` would.
+    pub fn tick_all_n(&mut self, n: u32) {
+        self.seed = xorshift(self.seed);
+        let tick_seed = self.seed;
+        // The calling thread ticks worlds itself; put its stream back after.
+        let caller_rng = rng_state();
+        let step = |(i, world): (usize, &mut World)| {
+            reseed_rng(xorshift(tick_seed ^ (i as u32).wrapping_mul(0x9E37_79B9)));
+            world.tick_n(n);
+        };
+        #[cfg(feature = "parallel")]
+        self.worlds.par_iter_mut().enumerate().for_each(step);
+        #[cfg(not(feature = "parallel"))]
+        self.worlds.iter_mut().enumerate().for_each(step);
+        reseed_rng(caller_rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell_idx, SPECIES_OIL, SPECIES_SAND, SPECIES_WALL};
+
+    fn arena(seed: u32) -> WorldArena {
+        let mut arena = WorldArena::with_seed(seed);
+        for n in 0..6 {
+            let mut world = World::new(48, 32);
+            for x in 0..48 {
+                world.set_cell(x, 31, SPECIES_WALL);
+                world.set_cell(x, n, if x % 2 == 0 { SPECIES_SAND } else { SPECIES_OIL });
+            }
+            arena.push(world);
+        }
+        arena
+    }
+
+    fn grids(arena: &WorldArena) -> Vec<Vec<u8>> {
+        arena.worlds().iter().map(|w| w.cells.to_vec()).collect()
+    }
+
+    #[test]
+    fn tick_all_steps_every_world() {
+        let mut arena = arena(7);
+        arena.tick_all_n(40);
+        for world in arena.worlds() {
+            assert_eq!(world.ticks(), 40);
+            assert_eq!(world.count_species(SPECIES_SAND), 24, "Sand is conserved");
+            assert!((0..48).all(|x| world.cells[cell_idx(48, x, 0)] != SPECIES_SAND), "Sand fell");
+        }
+    }
+
+    #[test]
+    fn runs_reproduce_from_the_seed() {
+        let mut a = arena(7);
+        let mut b = arena(7);
+        let mut c = arena(8);
+        for _ in 0..20 {
+            a.tick_all();
+            b.tick_all();
+            c.tick_all();
+        }
+        assert_eq!(grids(&a), grids(&b));
+        assert_ne!(grids(&a), grids(&c));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn thread_count_does_not_change_the_result() {
+        let run = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                let mut arena = arena(7);
+                for _ in 0..20 { arena.tick_all(); }
+                grids(&arena)
+            })
+        };
+        assert_eq!(run(1), run(4));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::properties::SpeciesProperties;
+use crate::{SPECIES_EMPTY, TEMP_AMBIENT};
+
+/// Bytes per background cell: species, temperature.
+pub(crate) const BG_STRIDE: usize = 2;
+
+/// Divisor on the inter-layer heat flow. Four times the in-plane divisor,
+/// so the backdrop only trickles heat to and from the simulation plane.
+const LAYER_COUPLING: i32 = 2048;
+
+/// Static plane of cells behind the simulation. Background cells never
+/// move or react; they only hold heat, pass it along to each other, and
+/// trade a little with the foreground cell in front of them.
+pub(crate) struct Background {
+    pub(crate) cells: Box<[u8]>,
+}
+
+impl Background {
+    pub(crate) fn new(width: usize, height: usize) -> Background {
+        Background { cells: vec![0; width * height * BG_STRIDE].into_boxed_slice() }
+    }
+
+    #[inline(always)]
+    pub(crate) fn species(&self, width: usize, x: usize, y: usize) -> u8 {
+        self.cells[(y * width + x) * BG_STRIDE]
+    }
+
+    pub(crate) fn set(&mut self, width: usize, x: usize, y: usize, species: u8) {
+        let i = (y * width + x) * BG_STRIDE;
+        self.cells[i] = species;
+        self.cells[i + 1] = if species == SPECIES_EMPTY { 0 } else { TEMP_AMBIENT };
+    }
+
+    /// One tick of heat flow: along the backdrop between neighboring
+    /// background cells, then across to the foreground. Empty background
+    /// cells take no part.
+    pub(crate) fn conduct(&mut self, fg: &mut [u8], width: usize, height: usize, props: &SpeciesProperties) {
+        let bg = &mut self.cells;
+        for y in 0..height {
+            for x in 0..width {
+                let i = (y * width + x) * BG_STRIDE;
+                let species = bg[i];
+                if species == SPECIES_EMPTY { continue; }
+                let cond = props.conductivity(species) as i32;
+
+                for &(nx, ny) in &[(x + 1, y), (x, y + 1)] {
+                    if nx >= width || ny >= height { continue; }
+                    let j = (ny * width + nx) * BG_STRIDE;
+                    if bg[j] == SPECIES_EMPTY { continue; }
+                    let min_cond = cond.min(props.conductivity(bg[j]) as i32);
+                    let delta = (bg[i + 1] as i32 - bg[j + 1] as i32) * min_cond / 512;
+                    bg[i + 1] = (bg[i + 1] as i32 - delta).clamp(0, 255) as u8;
+                    bg[j + 1] = (bg[j + 1] as i32 + delta).clamp(0, 255) as u8;
+                }
+
+                let f = (y * width + x) * crate::CELL_STRIDE;
+                let min_cond = cond.min(props.conductivity(fg[f]) as i32);
+                let delta = (fg[f + 2] as i32 - bg[i + 1] as i32) * min_cond / LAYER_COUPLING;
+                fg[f + 2] = (fg[f + 2] as i32 - delta).clamp(0, 255) as u8;
+                bg[i + 1] = (bg[i + 1] as i32 + delta).clamp(0, 255) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, CELL_STRIDE, SPECIES_LAVA, SPECIES_STONE, SPECIES_WALL};
+
+    fn bg_temp(bg: &Background, width: usize, x: usize, y: usize) -> u8 {
+        bg.cells[(y * width + x) * BG_STRIDE + 1]
+    }
+
+    #[test]
+    fn foreground_heat_leaks_into_backdrop() {
+        let mut fg = vec![0u8; 3 * 3 * CELL_STRIDE];
+        set_cell_raw(&mut fg, 3, 1, 1, SPECIES_LAVA, 0, 200, 0);
+        let mut bg = Background::new(3, 3);
+        bg.set(3, 1, 1, SPECIES_STONE);
+        for _ in 0..20 { bg.conduct(&mut fg, 3, 3, &SpeciesProperties::default()); }
+        assert!(bg_temp(&bg, 3, 1, 1) > TEMP_AMBIENT + 20);
+        assert!(fg[(3 + 1) * CELL_STRIDE + 2] < 200);
+    }
+
+    #[test]
+    fn insulating_backdrop_trades_less_heat() {
+        let run = |species: u8| {
+            let mut fg = vec![0u8; CELL_STRIDE];
+            set_cell_raw(&mut fg, 1, 0, 0, SPECIES_LAVA, 0, 200, 0);
+            let mut bg = Background::new(1, 1);
+            bg.set(1, 0, 0, species);
+            for _ in 0..20 { bg.conduct(&mut fg, 1, 1, &SpeciesProperties::default()); }
+            bg_temp(&bg, 1, 0, 0)
+        };
+        assert!(run(SPECIES_WALL) < run(SPECIES_STONE));
+    }
+
+    #[test]
+    fn heat_spreads_along_backdrop_only_through_cells() {
+        let mut fg = vec![0u8; 4 * CELL_STRIDE];
+        let mut bg = Background::new(4, 1);
+        bg.set(4, 0, 0, SPECIES_STONE);
+        bg.set(4, 1, 0, SPECIES_STONE);
+        bg.set(4, 3, 0, SPECIES_STONE);
+        bg.cells[1] = 200;
+        for _ in 0..10 { bg.conduct(&mut fg, 4, 1, &SpeciesProperties::default()); }
+        assert!(bg_temp(&bg, 4, 1, 0) > TEMP_AMBIENT + 20);
+        assert_eq!(bg_temp(&bg, 4, 3, 0), TEMP_AMBIENT, "The gap blocks conduction");
+    }
+}
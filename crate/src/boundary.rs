@@ -0,0 +1,191 @@
+use crate::chunks::ChunkMap;
+use crate::properties::SpeciesProperties;
+use crate::{
+    cell_idx, is_fluid, is_movable, is_pinned, pair_flow, rand_bool, set_clock, swap_cells, ConductionModel,
+    CONDUCTIVITY_CAP, SPECIES_EMPTY,
+    SPECIES_FIRE, SPECIES_GAS, SPECIES_HYDROGEN, SPECIES_SMOKE, SPECIES_STEAM, SPECIES_TOXIC_GAS,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// What happens at the edges of the world.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// The edges are solid: things pile up against them.
+    #[default]
+    Wall = 0,
+    /// The edges are open: powders and liquids fall out of the bottom,
+    /// gases rise out of the top, and fluids run out of the sides.
+    Open = 1,
+    /// The world is a torus: what leaves one edge comes back in at the
+    /// opposite one, and heat flows across the seams.
+    Wrap = 2,
+}
+
+/// Gases, which leave by the top edge rather than the bottom.
+#[inline(always)]
+fn rises(species: u8) -> bool {
+    matches!(species, SPECIES_STEAM | SPECIES_SMOKE | SPECIES_GAS | SPECIES_TOXIC_GAS | SPECIES_HYDROGEN)
+}
+
+/// Whether a cell on the bottom or top edge is heading out of it.
+#[inline(always)]
+fn leaves_bottom(species: u8) -> bool { is_movable(species) && !rises(species) }
+
+/// Fluids spread sideways, so they can leave by the side edges. Flames
+/// stay put.
+#[inline(always)]
+fn leaves_side(species: u8) -> bool { is_fluid(species) && species != SPECIES_FIRE }
+
+/// Moves or removes cells sitting on an edge they're heading out of. Runs
+/// once the movement pass is done: the walls held everything in for the
+/// tick, and this lets it through. Cells moved across a seam count as
+/// updated this tick.
+pub(crate) fn apply(cells: &mut [u8], width: usize, height: usize, boundary: Boundary, clock: u8, chunks: &mut ChunkMap) {
+    if boundary == Boundary::Wall || width == 0 || height == 0 { return; }
+    let (bottom, top) = (height - 1, 0);
+    for x in 0..width {
+        cross(cells, width, boundary, clock, chunks, (x, bottom), (x, top), leaves_bottom);
+        cross(cells, width, boundary, clock, chunks, (x, top), (x, bottom), rises);
+    }
+    let (left, right) = (0, width - 1);
+    for y in 0..height {
+        // Fluids spread either way at random, so only about half of those
+        // on the edge were heading out of it.
+        if rand_bool() {
+            cross(cells, width, boundary, clock, chunks, (left, y), (right, y), leaves_side);
+        } else {
+            cross(cells, width, boundary, clock, chunks, (right, y), (left, y), leaves_side);
+        }
+    }
+}
+
+/// Lets the cell at `from` out if `leaving` says it's heading that way:
+/// removed for an open edge, or moved to `to` on the opposite edge if
+/// there's room there for a wrapping one.
+#[allow(clippy::too_many_arguments)]
+fn cross(
+    cells: &mut [u8], width: usize, boundary: Boundary, clock: u8, chunks: &mut ChunkMap,
+    from: (usize, usize), to: (usize, usize), leaving: fn(u8) -> bool,
+) {
+    if from == to || !chunks.is_live(from.0, from.1) || !chunks.is_live(to.0, to.1) { return; }
+    let i = cell_idx(width, from.0, from.1);
+    if !leaving(cells[i]) || is_pinned(cells, i) { return; }
+    match boundary {
+        Boundary::Wall => return,
+        Boundary::Open => cells[i..i + crate::CELL_STRIDE].fill(0),
+        Boundary::Wrap => {
+            if cells[cell_idx(width, to.0, to.1)] != SPECIES_EMPTY { return; }
+            swap_cells(cells, width, from.0, from.1, to.0, to.1);
+            set_clock(cells, width, to.0, to.1, clock);
+            chunks.wake_area(to.0, to.1, 1);
+        }
+    }
+    chunks.wake_area(from.0, from.1, 1);
+}
+
+/// Heat flow across the seams of a wrapping world, between each edge cell
+/// and its neighbors on the opposite edge: the one facing it, and under
+/// the isotropic model the two diagonal to it as well. Matches
+/// `heat_conduction`'s pairwise exchange, so no heat is made or lost.
+pub(crate) fn conduct_seams(
+    cells: &mut [u8], width: usize, height: usize, props: &SpeciesProperties, model: ConductionModel,
+    chunks: &mut ChunkMap,
+) {
+    if width < 2 || height < 2 { return; }
+    let mut exchange = |a: (usize, usize), b: (usize, usize)| {
+        if !chunks.is_live(a.0, a.1) || !chunks.is_live(b.0, b.1) { return; }
+        let (ia, ib) = (cell_idx(width, a.0, a.1), cell_idx(width, b.0, b.1));
+        let cond = props.conductivity(cells[ia]).min(props.conductivity(cells[ib])).min(CONDUCTIVITY_CAP) as i32;
+        let flow = pair_flow(cells[ib + 2] as i32 - cells[ia + 2] as i32, cond);
+        if flow == 0 { return; }
+        cells[ia + 2] = (cells[ia + 2] as i32 + flow).clamp(0, 255) as u8;
+        cells[ib + 2] = (cells[ib + 2] as i32 - flow).clamp(0, 255) as u8;
+        chunks.mark_unsettled(a.0, a.1);
+        chunks.mark_unsettled(b.0, b.1);
+    };
+    for x in 0..width { exchange((x, 0), (x, height - 1)); }
+    for y in 0..height { exchange((0, y), (width - 1, y)); }
+    // Narrower than three, a diagonal across the seam is a cell already
+    // paired some other way.
+    if model == ConductionModel::Orthogonal || width < 3 || height < 3 { return; }
+    // Pairs across the top and bottom seam, the corners included, then
+    // those across only the side seam.
+    for x in 0..width {
+        exchange((x, 0), ((x + 1) % width, height - 1));
+        exchange(((x + 1) % width, 0), (x, height - 1));
+    }
+    for y in 0..height - 1 {
+        exchange((0, y), (width - 1, y + 1));
+        exchange((0, y + 1), (width - 1, y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_species, set_cell_raw, SPECIES_SAND, SPECIES_STONE, SPECIES_WALL, SPECIES_WATER};
+
+    fn grid(width: usize, height: usize) -> Vec<u8> { vec![0u8; width * height * crate::CELL_STRIDE] }
+
+    #[test]
+    fn wall_leaves_edges_alone() {
+        let mut cells = grid(3, 3);
+        set_cell_raw(&mut cells, 3, 1, 2, SPECIES_SAND, 0, 20, 0);
+        apply(&mut cells, 3, 3, Boundary::Wall, 1, &mut ChunkMap::new(3, 3));
+        assert_eq!(get_species(&cells, 3, 1, 2), SPECIES_SAND);
+    }
+
+    #[test]
+    fn open_bottom_drops_loose_cells_only() {
+        let mut cells = grid(3, 3);
+        set_cell_raw(&mut cells, 3, 0, 2, SPECIES_SAND, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 1, 2, SPECIES_WALL, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 1, 0, SPECIES_STEAM, 0, 120, 0);
+        apply(&mut cells, 3, 3, Boundary::Open, 1, &mut ChunkMap::new(3, 3));
+        assert_eq!(get_species(&cells, 3, 0, 2), SPECIES_EMPTY);
+        assert_eq!(get_species(&cells, 3, 1, 2), SPECIES_WALL);
+        assert_eq!(get_species(&cells, 3, 1, 0), SPECIES_EMPTY, "Gases leave by the top");
+    }
+
+    #[test]
+    fn wrap_carries_cells_to_the_opposite_edge_if_theres_room() {
+        let mut cells = grid(3, 3);
+        set_cell_raw(&mut cells, 3, 1, 2, SPECIES_SAND, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 2, 2, SPECIES_SAND, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 2, 0, SPECIES_STONE, 0, 20, 0);
+        apply(&mut cells, 3, 3, Boundary::Wrap, 1, &mut ChunkMap::new(3, 3));
+        assert_eq!(get_species(&cells, 3, 1, 0), SPECIES_SAND);
+        assert_eq!(get_species(&cells, 3, 2, 2), SPECIES_SAND, "Blocked on the far side");
+    }
+
+    #[test]
+    fn seams_conduct_without_losing_heat() {
+        let mut cells = grid(4, 2);
+        for c in cells.chunks_exact_mut(crate::CELL_STRIDE) { c[0] = SPECIES_WATER; c[2] = 20; }
+        cells[cell_idx(4, 0, 0) + 2] = 200;
+        let total = |cells: &[u8]| cells.chunks_exact(crate::CELL_STRIDE).map(|c| c[2] as u32).sum::<u32>();
+        let before = total(&cells);
+        conduct_seams(&mut cells, 4, 2, &SpeciesProperties::default(), ConductionModel::Isotropic, &mut ChunkMap::new(4, 2));
+        assert!(cells[cell_idx(4, 3, 0) + 2] > 20, "Heat crosses the side seam");
+        assert!(cells[cell_idx(4, 0, 1) + 2] > 20, "And the top and bottom one");
+        assert_eq!(total(&cells), before);
+    }
+
+    #[test]
+    fn seams_follow_the_conduction_model() {
+        let total = |cells: &[u8]| cells.chunks_exact(crate::CELL_STRIDE).map(|c| c[2] as u32).sum::<u32>();
+        for model in [ConductionModel::Isotropic, ConductionModel::Orthogonal] {
+            let mut cells = grid(4, 4);
+            for c in cells.chunks_exact_mut(crate::CELL_STRIDE) { c[0] = SPECIES_WATER; c[2] = 20; }
+            cells[cell_idx(4, 1, 0) + 2] = 200;
+            let before = total(&cells);
+            conduct_seams(&mut cells, 4, 4, &SpeciesProperties::default(), model, &mut ChunkMap::new(4, 4));
+            let diagonal = cells[cell_idx(4, 2, 3) + 2];
+            assert_eq!(diagonal > 20, model == ConductionModel::Isotropic, "{model:?}: diagonal at {diagonal}");
+            assert_eq!(total(&cells), before);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::chunks::ChunkMap;
+use crate::{CELL_STRIDE, SPECIES_EMPTY};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// What happens to the cells of a species once it goes over its cap.
+/// Every policy removes exactly the excess; they differ in which cells go.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Long-lived cells are culled first: those that sat in the same place
+    /// for the whole tick.
+    RemoveOldest = 0,
+    /// New arrivals are culled first: cells that appeared or moved this
+    /// tick. User placement is refused outright while at the cap.
+    BlockNew = 1,
+    /// Cells touching another of their kind are folded into it first; the
+    /// survivor keeps the hotter temperature.
+    Merge = 2,
+}
+
+/// Every species ID a cell can hold, built-in or custom.
+const SPECIES_IDS: usize = u8::MAX as usize + 1;
+
+#[derive(Clone, Copy)]
+struct Cap {
+    max: u32,
+    policy: OverflowPolicy,
+}
+
+/// Per-species population limits, enforced once at the end of each tick.
+pub(crate) struct Caps {
+    caps: [Option<Cap>; SPECIES_IDS],
+    /// Species byte of every cell at the start of the tick.
+    before: Box<[u8]>,
+    /// Population per species as of the last enforcement.
+    counts: [u32; SPECIES_IDS],
+}
+
+impl Caps {
+    pub(crate) fn new(width: usize, height: usize) -> Caps {
+        Caps {
+            caps: [None; SPECIES_IDS],
+            before: vec![SPECIES_EMPTY; width * height].into_boxed_slice(),
+            counts: [0; SPECIES_IDS],
+        }
+    }
+
+    pub(crate) fn set(&mut self, species: u8, max: u32, policy: OverflowPolicy) {
+        self.caps[species as usize] = Some(Cap { max, policy });
+    }
+
+    pub(crate) fn clear(&mut self, species: u8) { self.caps[species as usize] = None; }
+
+    pub(crate) fn is_empty(&self) -> bool { self.caps.iter().all(Option::is_none) }
+
+    /// Matches the snapshot buffer to a resized grid. Counts catch up at
+    /// the next enforcement.
+    pub(crate) fn resize(&mut self, width: usize, height: usize) {
+        self.before = vec![SPECIES_EMPTY; width * height].into_boxed_slice();
+    }
+
+    /// Whether the user may turn a `replacing` cell into `species` right
+    /// now. Counts the change if so; painting a species over itself changes
+    /// nothing and is always allowed.
+    pub(crate) fn admit(&mut self, species: u8, replacing: u8) -> bool {
+        if species == replacing { return true; }
+        match self.caps[species as usize] {
+            Some(cap) if cap.policy == OverflowPolicy::BlockNew && self.counts[species as usize] >= cap.max => false,
+            _ => {
+                self.counts[species as usize] += 1;
+                let old = &mut self.counts[replacing as usize];
+                *old = old.saturating_sub(1);
+                true
+            }
+        }
+    }
+
+    pub(crate) fn snapshot(&mut self, cells: &[u8]) {
+        for (b, c) in self.before.iter_mut().zip(cells.chunks_exact(CELL_STRIDE)) {
+            *b = c[0];
+        }
+    }
+
+    /// Recounts every species and removes the excess of any that are over
+    /// their cap. Removals are spread evenly over the preferred candidates
+    /// rather than taken from one end of the grid.
+    pub(crate) fn enforce(&mut self, cells: &mut [u8], width: usize, height: usize, chunks: &mut ChunkMap) {
+        self.counts = [0; SPECIES_IDS];
+        for c in cells.chunks_exact(CELL_STRIDE) { self.counts[c[0] as usize] += 1; }
+
+        for species in 0..=u8::MAX {
+            let Some(cap) = self.caps[species as usize] else { continue };
+            let count = self.counts[species as usize];
+            if count <= cap.max { continue; }
+            let excess = (count - cap.max) as usize;
+
+            let mut preferred = Vec::new();
+            let mut rest = Vec::new();
+            for cell in 0..width * height {
+                if cells[cell * CELL_STRIDE] != species { continue; }
+                let pick = match cap.policy {
+                    OverflowPolicy::RemoveOldest => self.before[cell] == species,
+                    OverflowPolicy::BlockNew => self.before[cell] != species,
+                    OverflowPolicy::Merge => merge_target(cells, width, height, cell, species).is_some(),
+                };
+                if pick { preferred.push(cell) } else { rest.push(cell) }
+            }
+
+            let mut victims = spread(&preferred, excess);
+            if victims.len() < excess {
+                victims.extend(spread(&rest, excess - victims.len()));
+            }
+            for cell in victims {
+                let i = cell * CELL_STRIDE;
+                if cap.policy == OverflowPolicy::Merge {
+                    if let Some(j) = merge_target(cells, width, height, cell, species) {
+                        cells[j + 2] = cells[j + 2].max(cells[i + 2]);
+                    }
+                }
+                cells[i..i + CELL_STRIDE].fill(0);
+                chunks.wake_area(cell % width, cell / width, 1);
+            }
+            self.counts[species as usize] = cap.max;
+        }
+    }
+}
+
+/// Byte index of an orthogonal neighbor of the same species, if any.
+fn merge_target(cells: &[u8], width: usize, height: usize, cell: usize, species: u8) -> Option<usize> {
+    let (x, y) = (cell % width, cell / width);
+    let neighbors = [
+        (x > 0).then(|| cell - 1),
+        (x + 1 < width).then(|| cell + 1),
+        (y > 0).then(|| cell - width),
+        (y + 1 < height).then(|| cell + width),
+    ];
+    neighbors.into_iter().flatten().map(|n| n * CELL_STRIDE).find(|&j| cells[j] == species)
+}
+
+/// Up to `n` entries picked at even spacing through `from`.
+fn spread(from: &[usize], n: usize) -> Vec<usize> {
+    if n == 0 || from.is_empty() { return Vec::new(); }
+    if n >= from.len() { return from.to_vec(); }
+    (0..n).map(|k| from[k * from.len() / n]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, SPECIES_SAND, SPECIES_STEAM};
+
+    fn count(cells: &[u8], species: u8) -> usize {
+        cells.chunks_exact(CELL_STRIDE).filter(|c| c[0] == species).count()
+    }
+
+    #[test]
+    fn spread_picks_evenly() {
+        let from: Vec<usize> = (0..10).collect();
+        assert_eq!(spread(&from, 2), vec![0, 5]);
+        assert_eq!(spread(&from, 20).len(), 10);
+    }
+
+    #[test]
+    fn enforce_trims_to_cap_and_prefers_by_policy() {
+        let (w, h) = (10, 2);
+        let mut cells = vec![0u8; w * h * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(w, h);
+        let mut caps = Caps::new(w, h);
+        for x in 0..5 { set_cell_raw(&mut cells, w, x, 0, SPECIES_STEAM, 0, 30, 0); }
+        caps.snapshot(&cells);
+        for x in 5..10 { set_cell_raw(&mut cells, w, x, 0, SPECIES_STEAM, 0, 30, 0); }
+
+        caps.set(SPECIES_STEAM, 6, OverflowPolicy::BlockNew);
+        caps.enforce(&mut cells, w, h, &mut chunks);
+        assert_eq!(count(&cells, SPECIES_STEAM), 6);
+        assert!((0..5).all(|x| cells[x * CELL_STRIDE] == SPECIES_STEAM), "Old steam survives BlockNew");
+    }
+
+    #[test]
+    fn remove_oldest_culls_settled_cells() {
+        let (w, h) = (10, 1);
+        let mut cells = vec![0u8; w * h * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(w, h);
+        let mut caps = Caps::new(w, h);
+        for x in 0..4 { set_cell_raw(&mut cells, w, x, 0, SPECIES_SAND, 0, 12, 0); }
+        caps.snapshot(&cells);
+        for x in 6..8 { set_cell_raw(&mut cells, w, x, 0, SPECIES_SAND, 0, 12, 0); }
+        caps.set(SPECIES_SAND, 2, OverflowPolicy::RemoveOldest);
+        caps.enforce(&mut cells, w, h, &mut chunks);
+        assert_eq!(count(&cells, SPECIES_SAND), 2);
+        assert!((6..8).all(|x| cells[x * CELL_STRIDE] == SPECIES_SAND));
+    }
+
+    #[test]
+    fn merge_keeps_the_hotter_temperature() {
+        let (w, h) = (2, 1);
+        let mut cells = vec![0u8; w * h * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(w, h);
+        let mut caps = Caps::new(w, h);
+        set_cell_raw(&mut cells, w, 0, 0, SPECIES_STEAM, 0, 90, 0);
+        set_cell_raw(&mut cells, w, 1, 0, SPECIES_STEAM, 0, 30, 0);
+        caps.set(SPECIES_STEAM, 1, OverflowPolicy::Merge);
+        caps.enforce(&mut cells, w, h, &mut chunks);
+        assert_eq!(count(&cells, SPECIES_STEAM), 1);
+        assert_eq!(cells[CELL_STRIDE + 2], 90);
+    }
+
+    #[test]
+    fn admit_blocks_only_block_new_at_cap() {
+        let mut caps = Caps::new(1, 1);
+        caps.set(SPECIES_SAND, 1, OverflowPolicy::BlockNew);
+        caps.set(SPECIES_STEAM, 1, OverflowPolicy::Merge);
+        assert!(caps.admit(SPECIES_SAND, SPECIES_EMPTY));
+        assert!(!caps.admit(SPECIES_SAND, SPECIES_EMPTY));
+        assert!(caps.admit(SPECIES_STEAM, SPECIES_EMPTY));
+        assert!(caps.admit(SPECIES_STEAM, SPECIES_EMPTY));
+    }
+
+    #[test]
+    fn admit_counts_only_real_changes() {
+        let mut caps = Caps::new(1, 1);
+        caps.set(SPECIES_SAND, 1, OverflowPolicy::BlockNew);
+        assert!(caps.admit(SPECIES_SAND, SPECIES_EMPTY));
+        for _ in 0..3 { assert!(caps.admit(SPECIES_SAND, SPECIES_SAND), "Repainting sand adds none"); }
+        assert!(caps.admit(SPECIES_STEAM, SPECIES_SAND));
+        assert!(caps.admit(SPECIES_SAND, SPECIES_EMPTY), "Painting over sand freed its place");
+    }
+
+    #[test]
+    fn custom_species_can_be_capped() {
+        let (w, h) = (4, 1);
+        let mut cells = vec![0u8; w * h * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(w, h);
+        let mut caps = Caps::new(w, h);
+        let custom = crate::CUSTOM_SPECIES_FIRST;
+        for x in 0..4 { set_cell_raw(&mut cells, w, x, 0, custom, 0, 12, 0); }
+        caps.set(custom, 1, OverflowPolicy::RemoveOldest);
+        caps.enforce(&mut cells, w, h, &mut chunks);
+        assert_eq!(count(&cells, custom), 1);
+    }
+}
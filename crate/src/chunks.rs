@@ -0,0 +1,315 @@
+use crate::BoundingBox;
+
+/// Side length of a chunk in cells.
+pub(crate) const CHUNK_SIZE: usize = 32;
+
+/// Coarse activity map over the grid. The movement pass skips chunks that
+/// were not woken during the previous tick, and the thermal passes skip
+/// chunks whose temperatures have stopped changing, so settled regions at
+/// rest cost next to nothing.
+pub(crate) struct ChunkMap {
+    cols: usize,
+    rows: usize,
+    /// Chunks updated during the current tick.
+    active: Box<[bool]>,
+    /// Chunks woken for the next tick.
+    next: Box<[bool]>,
+    /// Chunks updated during the previous tick.
+    prev: Box<[bool]>,
+    /// Chunks where the last conduction pass changed a temperature or left
+    /// a cell short of ambient.
+    unsettled: Box<[bool]>,
+    /// Chunks the thermal passes visit this tick.
+    thermal: Box<[bool]>,
+    /// Chunks active, unsettled or woken since the last `clear_dirty`:
+    /// anywhere a cell may have changed.
+    dirty: Box<[bool]>,
+    /// Cell rectangle `[x0, y0, x1, y1]` (exclusive end) outside which
+    /// nothing updates, if any.
+    sim_region: Option<[usize; 4]>,
+    /// Cell rectangles inside which nothing updates.
+    frozen: Vec<[usize; 4]>,
+}
+
+impl ChunkMap {
+    pub(crate) fn new(width: usize, height: usize) -> ChunkMap {
+        let cols = width.div_ceil(CHUNK_SIZE);
+        let rows = height.div_ceil(CHUNK_SIZE);
+        ChunkMap {
+            cols,
+            rows,
+            active: vec![true; cols * rows].into_boxed_slice(),
+            next: vec![true; cols * rows].into_boxed_slice(),
+            prev: vec![true; cols * rows].into_boxed_slice(),
+            unsettled: vec![true; cols * rows].into_boxed_slice(),
+            thermal: vec![true; cols * rows].into_boxed_slice(),
+            dirty: vec![false; cols * rows].into_boxed_slice(),
+            sim_region: None,
+            frozen: Vec::new(),
+        }
+    }
+
+    /// Promotes chunks woken since the last tick to active and resets the
+    /// wake flags for the tick about to run.
+    pub(crate) fn begin_tick(&mut self) {
+        std::mem::swap(&mut self.prev, &mut self.active);
+        std::mem::swap(&mut self.active, &mut self.next);
+        self.next.fill(false);
+    }
+
+    /// Cell rectangles `(x0, y0, x1, y1)` (exclusive end) of chunks that are
+    /// active this tick but slept through the previous one. Their update
+    /// clocks are stale and must be reset before the movement pass.
+    pub(crate) fn newly_woken(&self, width: usize, height: usize) -> impl Iterator<Item = (usize, usize, usize, usize)> + '_ {
+        (0..self.active.len())
+            .filter(|&c| self.active[c] && !self.prev[c])
+            .map(move |c| {
+                let x0 = (c % self.cols) * CHUNK_SIZE;
+                let y0 = (c / self.cols) * CHUNK_SIZE;
+                (x0, y0, (x0 + CHUNK_SIZE).min(width), (y0 + CHUNK_SIZE).min(height))
+            })
+    }
+
+    /// Column ranges `(x0, x1)` (exclusive end) of row `y` that lie in
+    /// active chunks, left to right.
+    pub(crate) fn active_spans(&self, y: usize, width: usize) -> impl DoubleEndedIterator<Item = (usize, usize)> + '_ {
+        spans(&self.active, self.cols, y, width)
+    }
+
+    /// Picks the chunks the thermal passes visit this tick: any chunk with
+    /// an unsettled or active chunk among itself and its eight neighbors,
+    /// since heat or a moved cell next door can start things changing
+    /// again. Everything when `everything` is set, for ticks where
+    /// temperatures may have changed behind the map's back. Call after
+    /// `begin_tick`.
+    pub(crate) fn plan_thermal(&mut self, everything: bool) {
+        for cy in 0..self.rows {
+            for cx in 0..self.cols {
+                let stirred = |c: usize| self.unsettled[c] || self.active[c];
+                let live = everything
+                    || (cy.saturating_sub(1)..=(cy + 1).min(self.rows - 1))
+                        .any(|ny| (cx.saturating_sub(1)..=(cx + 1).min(self.cols - 1)).any(|nx| stirred(ny * self.cols + nx)));
+                self.thermal[cy * self.cols + cx] = live;
+            }
+        }
+        // Visited chunks find out afresh whether they've settled.
+        for (u, &t) in self.unsettled.iter_mut().zip(self.thermal.iter()) {
+            if t { *u = false; }
+        }
+    }
+
+    /// Column ranges of row `y` in chunks the thermal passes visit.
+    pub(crate) fn thermal_spans(&self, y: usize, width: usize) -> impl DoubleEndedIterator<Item = (usize, usize)> + '_ {
+        spans(&self.thermal, self.cols, y, width)
+    }
+
+    #[inline(always)]
+    pub(crate) fn mark_unsettled(&mut self, x: usize, y: usize) {
+        self.unsettled[(y / CHUNK_SIZE) * self.cols + x / CHUNK_SIZE] = true;
+    }
+
+    /// Wakes every chunk overlapping the square of `radius` cells around (x, y).
+    #[inline]
+    pub(crate) fn wake_area(&mut self, x: usize, y: usize, radius: usize) {
+        if self.active.is_empty() { return; }
+        let cx0 = x.saturating_sub(radius) / CHUNK_SIZE;
+        let cy0 = y.saturating_sub(radius) / CHUNK_SIZE;
+        let cx1 = ((x + radius) / CHUNK_SIZE).min(self.cols - 1);
+        let cy1 = ((y + radius) / CHUNK_SIZE).min(self.rows - 1);
+        for cy in cy0..=cy1 {
+            for cx in cx0..=cx1 {
+                self.next[cy * self.cols + cx] = true;
+            }
+        }
+    }
+
+    /// Chunk grid dimensions `(cols, rows)`.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn dims(&self) -> (usize, usize) { (self.cols, self.rows) }
+
+    #[cfg(feature = "parallel")]
+    pub(crate) fn is_chunk_active(&self, cx: usize, cy: usize) -> bool {
+        self.active[cy * self.cols + cx]
+    }
+
+    /// A copy with the same active set and no pending wakes, for a worker
+    /// thread to record its wakes into. Fold it back with `merge`.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn fork(&self) -> ChunkMap {
+        ChunkMap {
+            cols: self.cols,
+            rows: self.rows,
+            active: self.active.clone(),
+            next: vec![false; self.next.len()].into_boxed_slice(),
+            prev: Box::new([]),
+            unsettled: Box::new([]),
+            thermal: Box::new([]),
+            dirty: Box::new([]),
+            sim_region: self.sim_region,
+            frozen: self.frozen.clone(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    pub(crate) fn merge(&mut self, other: &ChunkMap) {
+        for (n, &o) in self.next.iter_mut().zip(other.next.iter()) {
+            *n |= o;
+        }
+    }
+
+    pub(crate) fn set_sim_region(&mut self, region: Option<[usize; 4]>) { self.sim_region = region; }
+
+    pub(crate) fn freeze(&mut self, rect: [usize; 4]) { self.frozen.push(rect); }
+
+    pub(crate) fn thaw(&mut self) { self.frozen.clear(); }
+
+    /// Whether the cell at (x, y) updates at all: inside the sim region, if
+    /// there is one, and outside every frozen rectangle. Passes check this
+    /// on top of chunk activity.
+    #[inline(always)]
+    pub(crate) fn is_live(&self, x: usize, y: usize) -> bool {
+        let inside = |r: &[usize; 4]| x >= r[0] && y >= r[1] && x < r[2] && y < r[3];
+        self.sim_region.as_ref().is_none_or(inside) && !self.frozen.iter().any(inside)
+    }
+
+    /// Adds the chunks active, unsettled or woken so far this tick to the
+    /// dirty set.
+    pub(crate) fn mark_dirty(&mut self) {
+        for (c, d) in self.dirty.iter_mut().enumerate() {
+            *d |= self.active[c] || self.unsettled[c] || self.next[c];
+        }
+    }
+
+    pub(crate) fn clear_dirty(&mut self) { self.dirty.fill(false); }
+
+    /// The dirty set as cell rectangles, one per run of dirty chunks along
+    /// each chunk row, top to bottom.
+    pub(crate) fn dirty_rects(&self, width: usize, height: usize) -> Vec<BoundingBox> {
+        let mut rects = Vec::new();
+        for cy in 0..self.rows {
+            let y = cy * CHUNK_SIZE;
+            let max_y = (y + CHUNK_SIZE).min(height) - 1;
+            let mut run: Option<(usize, usize)> = None;
+            for (x0, x1) in spans(&self.dirty, self.cols, y, width) {
+                run = match run {
+                    Some((start, end)) if end == x0 => Some((start, x1)),
+                    Some((start, end)) => {
+                        rects.push(BoundingBox { min_x: start, min_y: y, max_x: end - 1, max_y });
+                        Some((x0, x1))
+                    }
+                    None => Some((x0, x1)),
+                };
+            }
+            if let Some((start, end)) = run {
+                rects.push(BoundingBox { min_x: start, min_y: y, max_x: end - 1, max_y });
+            }
+        }
+        rects
+    }
+
+    pub(crate) fn wake_all(&mut self) {
+        self.next.fill(true);
+    }
+
+    pub(crate) fn active_count(&self) -> usize {
+        self.active.iter().filter(|&&a| a).count()
+    }
+
+    pub(crate) fn thermal_count(&self) -> usize {
+        self.thermal.iter().filter(|&&t| t).count()
+    }
+}
+
+/// Runs of flagged chunks along the chunk row holding `y`, as cell columns.
+fn spans(flags: &[bool], cols: usize, y: usize, width: usize) -> impl DoubleEndedIterator<Item = (usize, usize)> + '_ {
+    let row = &flags[(y / CHUNK_SIZE) * cols..][..cols];
+    (0..cols).filter(move |&cx| row[cx]).map(move |cx| (cx * CHUNK_SIZE, ((cx + 1) * CHUNK_SIZE).min(width)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_map_starts_fully_active() {
+        let mut map = ChunkMap::new(70, 40);
+        assert_eq!(map.active_count(), 3 * 2);
+        map.begin_tick();
+        assert_eq!(map.active_count(), 3 * 2);
+        map.begin_tick();
+        assert_eq!(map.active_count(), 0);
+    }
+
+    #[test]
+    fn wake_area_spills_into_neighbor_chunks() {
+        let mut map = ChunkMap::new(64, 64);
+        map.begin_tick();
+        map.begin_tick();
+        map.wake_area(31, 10, 2);
+        map.begin_tick();
+        assert_eq!(map.active_spans(0, 64).collect::<Vec<_>>(), vec![(0, 32), (32, 64)]);
+        assert_eq!(map.active_spans(32, 64).count(), 0);
+        assert_eq!(map.active_count(), 2);
+        assert_eq!(map.newly_woken(64, 64).collect::<Vec<_>>(), vec![(0, 0, 32, 32), (32, 0, 64, 32)]);
+        map.wake_area(5, 5, 0);
+        map.begin_tick();
+        assert_eq!(map.newly_woken(64, 64).count(), 0);
+    }
+
+    #[test]
+    fn thermal_passes_cover_the_neighbors_of_anything_stirring() {
+        let mut map = ChunkMap::new(32 * 5, 32 * 5);
+        map.begin_tick();
+        map.plan_thermal(false);
+        assert_eq!(map.thermal_count(), 25, "A new map is unsettled everywhere");
+        map.begin_tick();
+        map.plan_thermal(false);
+        assert_eq!(map.thermal_count(), 0, "Nothing was marked unsettled");
+
+        map.mark_unsettled(70, 70);
+        map.wake_area(150, 0, 0);
+        map.begin_tick();
+        map.plan_thermal(false);
+        assert_eq!(map.thermal_count(), 9 + 3, "The two blocks share chunk (3, 1)");
+        assert_eq!(map.thermal_spans(0, 150).collect::<Vec<_>>(), vec![(96, 128), (128, 150)]);
+        assert_eq!(map.thermal_spans(64, 160).collect::<Vec<_>>(), vec![(32, 64), (64, 96), (96, 128)]);
+        assert_eq!(map.active_spans(0, 150).collect::<Vec<_>>(), vec![(128, 150)]);
+
+        map.begin_tick();
+        map.plan_thermal(true);
+        assert_eq!(map.thermal_count(), 25);
+    }
+
+    #[test]
+    fn dirty_chunks_merge_into_rects_along_each_row() {
+        let mut map = ChunkMap::new(32 * 4, 40);
+        map.begin_tick();
+        map.plan_thermal(false);
+        map.begin_tick();
+        map.plan_thermal(false);
+        map.clear_dirty();
+        map.wake_area(10, 5, 0);
+        map.wake_area(40, 5, 0);
+        map.wake_area(100, 35, 0);
+        map.mark_dirty();
+        assert_eq!(map.dirty_rects(128, 40), vec![
+            BoundingBox { min_x: 0, min_y: 0, max_x: 63, max_y: 31 },
+            BoundingBox { min_x: 96, min_y: 32, max_x: 127, max_y: 39 },
+        ]);
+        map.clear_dirty();
+        assert!(map.dirty_rects(128, 40).is_empty());
+    }
+
+    #[test]
+    fn frozen_rectangles_cut_holes_in_the_sim_region() {
+        let mut map = ChunkMap::new(64, 64);
+        assert!(map.is_live(63, 63));
+        map.set_sim_region(Some([10, 10, 50, 50]));
+        map.freeze([20, 20, 30, 30]);
+        assert!(map.is_live(10, 10) && map.is_live(49, 49) && map.is_live(30, 25));
+        assert!(!map.is_live(50, 49) && !map.is_live(9, 20), "Outside the sim region");
+        assert!(!map.is_live(20, 20) && !map.is_live(29, 29), "Frozen");
+        map.thaw();
+        assert!(map.is_live(25, 25));
+    }
+}
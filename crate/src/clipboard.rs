@@ -0,0 +1,143 @@
+use crate::{CELL_STRIDE, SPECIES_EMPTY};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// How a pasted clipboard combines with the cells already there.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasteMode {
+    /// Every cell of the clipboard replaces what is under it, empty
+    /// cells included.
+    Overwrite = 0,
+    /// Empty cells of the clipboard leave the world alone, so a structure
+    /// can be dropped into a scene without carving out its bounding box.
+    SkipEmpty = 1,
+}
+
+/// A rectangle of raw cells lifted out of a world by `World::copy_region`,
+/// ready to be transformed and pasted back with `World::paste`. Cells keep
+/// their full state: species, color noise, temperature and aux bytes.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clipboard {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl Clipboard {
+    pub(crate) fn new(width: usize, height: usize, cells: Vec<u8>) -> Clipboard {
+        debug_assert_eq!(cells.len(), width * height * CELL_STRIDE);
+        Clipboard { width, height, cells }
+    }
+
+    /// The `CELL_STRIDE` bytes of the cell at (x, y).
+    pub(crate) fn cell(&self, x: usize, y: usize) -> &[u8] {
+        let i = (y * self.width + x) * CELL_STRIDE;
+        &self.cells[i..i + CELL_STRIDE]
+    }
+
+    /// Rebuilds the cells into a `width` x `height` grid, taking each new
+    /// cell from the old position `from(x, y)` returns.
+    fn remap(&mut self, width: usize, height: usize, from: impl Fn(usize, usize) -> (usize, usize)) {
+        let mut cells = vec![0; self.cells.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = from(x, y);
+                let dst = (y * width + x) * CELL_STRIDE;
+                cells[dst..dst + CELL_STRIDE].copy_from_slice(self.cell(sx, sy));
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Clipboard {
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    /// True if the clipboard holds no cells, or only empty ones.
+    pub fn is_empty(&self) -> bool {
+        self.cells.chunks_exact(CELL_STRIDE).all(|c| c[0] == SPECIES_EMPTY)
+    }
+
+    /// Turns the contents a quarter turn clockwise, swapping width and
+    /// height.
+    pub fn rotate90(&mut self) {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x));
+    }
+
+    /// Mirrors the contents left to right.
+    pub fn flip_horizontal(&mut self) {
+        let w = self.width;
+        self.remap(self.width, self.height, |x, y| (w - 1 - x, y));
+    }
+
+    /// Mirrors the contents top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let h = self.height;
+        self.remap(self.width, self.height, |x, y| (x, h - 1 - y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clipboard whose cells carry their own index in the species byte.
+    fn numbered(width: usize, height: usize) -> Clipboard {
+        let mut cells = vec![0; width * height * CELL_STRIDE];
+        for (i, c) in cells.chunks_exact_mut(CELL_STRIDE).enumerate() {
+            c[0] = i as u8 + 1;
+            c[2] = 100 + i as u8;
+        }
+        Clipboard::new(width, height, cells)
+    }
+
+    fn species(clip: &Clipboard) -> Vec<u8> {
+        clip.cells.chunks_exact(CELL_STRIDE).map(|c| c[0]).collect()
+    }
+
+    #[test]
+    fn rotate90_turns_clockwise() {
+        // 1 2 3      4 1
+        // 4 5 6  ->  5 2
+        //            6 3
+        let mut clip = numbered(3, 2);
+        clip.rotate90();
+        assert_eq!((clip.width(), clip.height()), (2, 3));
+        assert_eq!(species(&clip), [4, 1, 5, 2, 6, 3]);
+        assert_eq!(clip.cell(1, 0)[2], 100, "Whole cells move, not just the species");
+    }
+
+    #[test]
+    fn four_rotations_are_the_identity() {
+        let original = numbered(4, 3);
+        let mut clip = original.clone();
+        for _ in 0..4 { clip.rotate90(); }
+        assert_eq!(clip, original);
+    }
+
+    #[test]
+    fn flips_mirror_each_axis() {
+        let mut clip = numbered(3, 2);
+        clip.flip_horizontal();
+        assert_eq!(species(&clip), [3, 2, 1, 6, 5, 4]);
+        clip.flip_vertical();
+        assert_eq!(species(&clip), [6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn empty_clipboards_transform_safely() {
+        let mut clip = Clipboard::new(0, 0, Vec::new());
+        clip.rotate90();
+        clip.flip_horizontal();
+        clip.flip_vertical();
+        assert!(clip.is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::custom::{CustomSpecies, CUSTOM_SPECIES_LAST};
+use crate::properties::SpeciesProperties;
+use crate::{SPECIES_CHARCOAL, SPECIES_OIL};
+
+/// What a fire cell leaves behind when its fuel runs out. The three
+/// chances are rolled in order (charcoal, then ash, then smoke); whatever
+/// is left over burns away to empty space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BurnProducts {
+    /// Chance the cell turns into smoke.
+    pub smoke: f32,
+    /// Chance the cell crumbles into ash.
+    pub ash: f32,
+    /// Chance the cell chars into (re-ignitable) charcoal.
+    pub charcoal: f32,
+    /// Heat added to every neighbor when the flame dies.
+    pub leftover_heat: u8,
+}
+
+impl BurnProducts {
+    /// Matches the original hard-coded burn-out: 60% smoke, 40% nothing.
+    pub const DEFAULT: BurnProducts = BurnProducts { smoke: 0.6, ash: 0.0, charcoal: 0.0, leftover_heat: 0 };
+}
+
+impl Default for BurnProducts {
+    fn default() -> Self { BurnProducts::DEFAULT }
+}
+
+/// Chance per tick that a clone block fills each empty neighbor.
+pub const CLONE_RATE_DEFAULT: f32 = 0.1;
+
+/// Per-species tunables owned by a `World`. Indexed by species ID.
+#[derive(Clone, Debug)]
+pub struct SpeciesConfig {
+    burn: [BurnProducts; CUSTOM_SPECIES_LAST as usize + 1],
+    clone_rate: f32,
+    acid_affects_walls: bool,
+    oxygen_model: bool,
+    properties: SpeciesProperties,
+    custom: CustomSpecies,
+}
+
+impl SpeciesConfig {
+    /// Burn products for fire fed by `fuel`. Fire with no known origin
+    /// (e.g. painted by the user) uses the entry for `SPECIES_EMPTY`.
+    #[inline]
+    pub fn burn_products(&self, fuel: u8) -> BurnProducts {
+        self.burn.get(fuel as usize).copied().unwrap_or_default()
+    }
+
+    /// Replaces the burn products for `fuel`. Ignores unknown species.
+    pub fn set_burn_products(&mut self, fuel: u8, products: BurnProducts) {
+        if let Some(slot) = self.burn.get_mut(fuel as usize) {
+            *slot = BurnProducts {
+                smoke: products.smoke.clamp(0.0, 1.0),
+                ash: products.ash.clamp(0.0, 1.0),
+                charcoal: products.charcoal.clamp(0.0, 1.0),
+                leftover_heat: products.leftover_heat,
+            };
+        }
+    }
+
+    #[inline]
+    pub fn clone_rate(&self) -> f32 { self.clone_rate }
+
+    pub fn set_clone_rate(&mut self, rate: f32) {
+        self.clone_rate = rate.clamp(0.0, 1.0);
+    }
+
+    /// Whether acid slowly eats through walls as well.
+    #[inline]
+    pub fn acid_affects_walls(&self) -> bool { self.acid_affects_walls }
+
+    pub fn set_acid_affects_walls(&mut self, enabled: bool) {
+        self.acid_affects_walls = enabled;
+    }
+
+    /// Whether fire needs open air or oxygen beside it to keep burning.
+    #[inline]
+    pub fn oxygen_model(&self) -> bool { self.oxygen_model }
+
+    pub fn set_oxygen_model(&mut self, enabled: bool) {
+        self.oxygen_model = enabled;
+    }
+
+    /// The `SpeciesProperty` table: conductivity, ignition, fuel, density,
+    /// flow and corrosion of every species.
+    #[inline]
+    pub fn properties(&self) -> &SpeciesProperties { &self.properties }
+
+    pub fn properties_mut(&mut self) -> &mut SpeciesProperties { &mut self.properties }
+
+    /// Behaviors plugged in for the custom species range.
+    #[inline]
+    pub(crate) fn custom(&self) -> &CustomSpecies { &self.custom }
+
+    pub(crate) fn custom_mut(&mut self) -> &mut CustomSpecies { &mut self.custom }
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        let mut burn = [BurnProducts::DEFAULT; CUSTOM_SPECIES_LAST as usize + 1];
+        burn[SPECIES_CHARCOAL as usize] = BurnProducts { smoke: 0.2, ash: 0.5, charcoal: 0.0, leftover_heat: 4 };
+        // Oil burns dirty: nearly every flame leaves a puff of smoke.
+        burn[SPECIES_OIL as usize] = BurnProducts { smoke: 0.95, ash: 0.0, charcoal: 0.0, leftover_heat: 0 };
+        SpeciesConfig {
+            burn,
+            clone_rate: CLONE_RATE_DEFAULT,
+            acid_affects_walls: false,
+            oxygen_model: false,
+            properties: SpeciesProperties::default(),
+            custom: CustomSpecies::default(),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use crate::chunks::ChunkMap;
+use crate::properties::Properties;
+use crate::query::CellInfo;
+use crate::{
+    cell_idx, in_bounds, initial_state, set_cell_raw, set_clock, swap_cells, CELL_STRIDE, SPECIES_COUNT,
+    SPECIES_WALL,
+};
+
+/// First and last species ID free for custom species, whose behavior is
+/// plugged in at runtime rather than built into the simulation.
+pub const CUSTOM_SPECIES_FIRST: u8 = 64;
+pub const CUSTOM_SPECIES_LAST: u8 = 127;
+
+/// Whether `species` can name a species at all: a built-in one, or any ID
+/// in the custom range, registered or not. For data that isn't tied to a
+/// world, like saves and prefabs; a world checks `CustomSpecies::is_known`.
+#[inline]
+pub(crate) fn is_species_id(species: u8) -> bool {
+    (species as usize) < SPECIES_COUNT || (CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).contains(&species)
+}
+
+/// What a custom species' update sees: the 3x3 block of cells around it.
+/// Cells past the edge of the grid read as wall, so nothing tries to move
+/// there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Neighborhood {
+    cells: [CellInfo; 9],
+}
+
+impl Neighborhood {
+    pub(crate) fn gather(cells: &[u8], width: usize, height: usize, x: usize, y: usize) -> Neighborhood {
+        let mut wall = [0u8; CELL_STRIDE];
+        wall[0] = SPECIES_WALL;
+        let mut out = [CellInfo::from_bytes(&wall); 9];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if !in_bounds(width, height, nx, ny) { continue; }
+                let i = cell_idx(width, nx as usize, ny as usize);
+                out[slot(dx, dy)] = CellInfo::from_bytes(&cells[i..i + CELL_STRIDE]);
+            }
+        }
+        Neighborhood { cells: out }
+    }
+
+    /// The cell at offset (dx, dy) from the center, each in -1..=1. Offsets
+    /// further out are clamped.
+    pub fn get(&self, dx: i32, dy: i32) -> CellInfo {
+        self.cells[slot(dx.clamp(-1, 1) as isize, dy.clamp(-1, 1) as isize)]
+    }
+
+    /// The cell being updated.
+    pub fn center(&self) -> CellInfo { self.cells[4] }
+}
+
+#[inline(always)]
+fn slot(dx: isize, dy: isize) -> usize {
+    ((dy + 1) * 3 + dx + 1) as usize
+}
+
+/// One change a custom species' update asks for, at an offset in -1..=1
+/// from the updating cell. Writes are applied in order; ones that fall
+/// off the grid, land outside the 3x3 neighborhood the update started
+/// from, or name an unknown species are dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellWrite {
+    /// Replaces the cell with a fresh one of `species`, as if placed by hand.
+    Species { dx: i8, dy: i8, species: u8 },
+    /// Swaps the updating cell with this one: how a custom species moves.
+    /// The offset is from wherever the updating cell is by then.
+    Swap { dx: i8, dy: i8 },
+    Temperature { dx: i8, dy: i8, temp: u8 },
+    Ra { dx: i8, dy: i8, ra: u8 },
+}
+
+/// The update run once per tick for every cell of a custom species.
+pub type UpdateFn = dyn Fn(&Neighborhood) -> Vec<CellWrite> + Send + Sync;
+
+pub(crate) type Behavior = Arc<UpdateFn>;
+
+/// A particle behavior plugged in from Rust with `World::register_species`.
+pub struct SpeciesDef {
+    /// Run once per tick for every cell of the species, from inside the
+    /// movement pass; returns the changes to make around it.
+    pub update_fn: Box<UpdateFn>,
+    /// How the rest of the simulation treats the species: heat flow,
+    /// burning, acid and so on. `Properties::INERT` opts out of all of it.
+    pub properties: Properties,
+}
+
+/// Behaviors registered for the custom species range.
+#[derive(Clone, Default)]
+pub(crate) struct CustomSpecies {
+    behaviors: Vec<Option<Behavior>>,
+}
+
+impl std::fmt::Debug for CustomSpecies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let registered: Vec<usize> = (0..self.behaviors.len())
+            .filter(|&i| self.behaviors[i].is_some())
+            .map(|i| i + CUSTOM_SPECIES_FIRST as usize)
+            .collect();
+        f.debug_struct("CustomSpecies").field("registered", &registered).finish()
+    }
+}
+
+impl CustomSpecies {
+    pub(crate) fn register(&mut self, species: u8, behavior: Behavior) -> Result<(), String> {
+        if !(CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).contains(&species) {
+            return Err(format!(
+                "custom species must be in {CUSTOM_SPECIES_FIRST}..={CUSTOM_SPECIES_LAST}, got {species}"
+            ));
+        }
+        let slot = (species - CUSTOM_SPECIES_FIRST) as usize;
+        if self.behaviors.len() <= slot { self.behaviors.resize(slot + 1, None); }
+        self.behaviors[slot] = Some(behavior);
+        Ok(())
+    }
+
+    pub(crate) fn behavior(&self, species: u8) -> Option<&Behavior> {
+        let slot = species.checked_sub(CUSTOM_SPECIES_FIRST)? as usize;
+        self.behaviors.get(slot)?.as_ref()
+    }
+
+    /// The lowest custom ID with nothing registered, if any are left.
+    pub(crate) fn next_free(&self) -> Option<u8> {
+        (CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).find(|&s| !self.is_registered(s))
+    }
+
+    #[inline]
+    pub(crate) fn is_registered(&self, species: u8) -> bool { self.behavior(species).is_some() }
+
+    /// Built-in species and registered custom ones.
+    #[inline]
+    pub(crate) fn is_known(&self, species: u8) -> bool {
+        (species as usize) < SPECIES_COUNT || self.is_registered(species)
+    }
+}
+
+/// Runs a custom species' behavior for the cell at (x, y) and applies the
+/// writes it returns. Custom cells keep their chunk awake, since there's
+/// no telling when a behavior has gone idle.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update(
+    cells: &mut [u8], width: usize, height: usize, x: usize, y: usize, clock: u8,
+    behavior: &Behavior, custom: &CustomSpecies, chunks: &mut ChunkMap,
+) {
+    let writes = behavior(&Neighborhood::gather(cells, width, height, x, y));
+    let (mut cx, mut cy) = (x, y);
+    for write in writes {
+        let (dx, dy) = match write {
+            CellWrite::Species { dx, dy, .. } | CellWrite::Swap { dx, dy }
+            | CellWrite::Temperature { dx, dy, .. } | CellWrite::Ra { dx, dy, .. } => (dx, dy),
+        };
+        if !(-1..=1).contains(&dx) || !(-1..=1).contains(&dy) { continue; }
+        let (nx, ny) = (cx as isize + dx as isize, cy as isize + dy as isize);
+        if !in_bounds(width, height, nx, ny) { continue; }
+        // Chained swaps could otherwise walk the cell any distance.
+        if nx.abs_diff(x as isize) > 1 || ny.abs_diff(y as isize) > 1 { continue; }
+        let (nx, ny) = (nx as usize, ny as usize);
+        let i = cell_idx(width, nx, ny);
+        match write {
+            CellWrite::Species { species, .. } if custom.is_known(species) => {
+                let (ra, temp) = initial_state(species);
+                set_cell_raw(cells, width, nx, ny, species, ra, temp, clock);
+            }
+            CellWrite::Species { .. } => {}
+            CellWrite::Swap { .. } => {
+                swap_cells(cells, width, cx, cy, nx, ny);
+                set_clock(cells, width, cx, cy, clock);
+                set_clock(cells, width, nx, ny, clock);
+                (cx, cy) = (nx, ny);
+            }
+            CellWrite::Temperature { temp, .. } => cells[i + 2] = temp,
+            CellWrite::Ra { ra, .. } => cells[i + 1] = ra,
+        }
+    }
+    chunks.wake_area(cx, cy, 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_EMPTY, SPECIES_SAND};
+
+    const CRAWLER: u8 = CUSTOM_SPECIES_FIRST;
+
+    /// Moves right into empty space, warming whatever it leaves behind.
+    fn crawler() -> Behavior {
+        Arc::new(|n: &Neighborhood| {
+            if n.get(1, 0).species != SPECIES_EMPTY { return Vec::new(); }
+            vec![CellWrite::Swap { dx: 1, dy: 0 }, CellWrite::Temperature { dx: -1, dy: 0, temp: 99 }]
+        })
+    }
+
+    #[test]
+    fn register_checks_the_range() {
+        let mut custom = CustomSpecies::default();
+        assert!(custom.register(SPECIES_SAND, crawler()).is_err());
+        assert!(custom.register(CUSTOM_SPECIES_LAST + 1, crawler()).is_err());
+        assert!(custom.register(CRAWLER, crawler()).is_ok());
+        assert!(custom.is_known(CRAWLER) && custom.is_known(SPECIES_SAND));
+        assert!(!custom.is_known(CRAWLER + 1));
+    }
+
+    #[test]
+    fn neighborhood_reads_walls_past_the_edge() {
+        let mut cells = vec![0u8; 2 * 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 1, 1, SPECIES_SAND, 3, 40, 0);
+        let n = Neighborhood::gather(&cells, 2, 2, 0, 0);
+        assert_eq!(n.get(-1, 0).species, SPECIES_WALL);
+        assert_eq!((n.get(1, 1).species, n.get(1, 1).temperature), (SPECIES_SAND, 40));
+        assert_eq!(n.get(5, 5), n.get(1, 1), "Offsets are clamped");
+    }
+
+    #[test]
+    fn writes_follow_the_moving_cell() {
+        let mut custom = CustomSpecies::default();
+        custom.register(CRAWLER, crawler()).unwrap();
+        let mut cells = vec![0u8; 3 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 3, 0, 0, CRAWLER, 0, 20, 0);
+        let behavior = custom.behavior(CRAWLER).unwrap().clone();
+        update(&mut cells, 3, 1, 0, 0, 1, &behavior, &custom, &mut ChunkMap::new(3, 1));
+        assert_eq!(cells[cell_idx(3, 1, 0)], CRAWLER);
+        assert_eq!(cells[cell_idx(3, 0, 0) + 2], 99, "Offsets are from the cell's new place");
+    }
+
+    #[test]
+    fn chained_swaps_stay_in_the_neighborhood() {
+        let mut custom = CustomSpecies::default();
+        let runner: Behavior = Arc::new(|_: &Neighborhood| vec![CellWrite::Swap { dx: 1, dy: 0 }; 5]);
+        custom.register(CRAWLER, runner).unwrap();
+        let mut cells = vec![0u8; 8 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 8, 0, 0, CRAWLER, 0, 20, 0);
+        let behavior = custom.behavior(CRAWLER).unwrap().clone();
+        update(&mut cells, 8, 1, 0, 0, 1, &behavior, &custom, &mut ChunkMap::new(8, 1));
+        assert_eq!(cells[cell_idx(8, 1, 0)], CRAWLER, "One step, then the rest are dropped");
+    }
+}
@@ -0,0 +1,109 @@
+use crate::rigid::RIGID;
+use crate::{CELL_STRIDE, SPECIES_EMPTY, SPECIES_PLANT, SPECIES_SAND, SPECIES_STONE};
+
+/// Set in `ra` to mark a decorated cell; the low bits pick the variant.
+/// Plain color noise (`rand_ra`) never reaches this bit.
+pub(crate) const DECOR_FLAG: u8 = 0x80;
+/// Variant bits, clear of the flag above and the rigid-body flag.
+const VARIANT_MASK: u8 = 0x3F;
+
+/// Fraction of eligible cells that get a decoration, out of 65536.
+const ORE_RATE: u32 = 2000;
+const FLOWER_RATE: u32 = 6000;
+const PEBBLE_RATE: u32 = 1500;
+
+/// Stable 32-bit hash of a coordinate pair and seed.
+pub(crate) fn hash32(x: usize, y: usize, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x9E37_79B9) ^ (y as u32).wrapping_mul(0x85EB_CA6B) ^ seed;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^ (h >> 16)
+}
+
+#[inline(always)]
+pub(crate) fn is_decorated(ra: u8) -> bool {
+    ra & DECOR_FLAG != 0
+}
+
+#[inline(always)]
+pub(crate) fn variant(ra: u8) -> u8 {
+    ra & VARIANT_MASK
+}
+
+/// Sprinkles ore flecks in stone, flowers on exposed plant tops, and
+/// pebbles in sand. Which cells are picked depends only on the position,
+/// species and `seed`, so the same world and seed always decorate alike
+/// and the simulation's RNG stream is left untouched.
+pub(crate) fn decorate(cells: &mut [u8], width: usize, height: usize, seed: u32) {
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) * CELL_STRIDE;
+            let rate = match cells[i] {
+                SPECIES_STONE => ORE_RATE,
+                SPECIES_SAND => PEBBLE_RATE,
+                SPECIES_PLANT if y > 0 && cells[i - width * CELL_STRIDE] == SPECIES_EMPTY => FLOWER_RATE,
+                _ => continue,
+            };
+            let h = hash32(x, y, seed);
+            if h & 0xFFFF < rate {
+                cells[i + 1] = DECOR_FLAG | (cells[i + 1] & RIGID) | (h >> 16) as u8 & VARIANT_MASK;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, SPECIES_WATER};
+
+    fn field(width: usize, height: usize) -> Vec<u8> {
+        let mut cells = vec![0u8; width * height * CELL_STRIDE];
+        for y in 0..height {
+            for x in 0..width {
+                let species = if y < height / 2 { SPECIES_STONE } else { SPECIES_WATER };
+                set_cell_raw(&mut cells, width, x, y, species, 5, 20, 0);
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn same_seed_decorates_alike() {
+        let mut a = field(64, 64);
+        let mut b = field(64, 64);
+        decorate(&mut a, 64, 64, 7);
+        decorate(&mut b, 64, 64, 7);
+        assert_eq!(a, b);
+        let mut c = field(64, 64);
+        decorate(&mut c, 64, 64, 8);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn only_eligible_species_are_marked() {
+        let mut cells = field(64, 64);
+        decorate(&mut cells, 64, 64, 1);
+        let marked = |species: u8| {
+            cells.chunks_exact(CELL_STRIDE).filter(|c| c[0] == species && is_decorated(c[1])).count()
+        };
+        assert!(marked(SPECIES_STONE) > 0);
+        assert_eq!(marked(SPECIES_WATER), 0);
+    }
+
+    #[test]
+    fn flowers_only_grow_on_exposed_plant() {
+        let (w, h) = (200, 3);
+        let mut cells = vec![0u8; w * h * CELL_STRIDE];
+        for x in 0..w {
+            set_cell_raw(&mut cells, w, x, 1, SPECIES_PLANT, 3, 20, 0);
+            set_cell_raw(&mut cells, w, x, 2, SPECIES_PLANT, 3, 20, 0);
+        }
+        decorate(&mut cells, w, h, 3);
+        let row = |y: usize| (0..w).filter(|&x| is_decorated(cells[((y * w) + x) * CELL_STRIDE + 1])).count();
+        assert!(row(1) > 0);
+        assert_eq!(row(2), 0, "Buried plant stays plain");
+    }
+}
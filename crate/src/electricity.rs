@@ -0,0 +1,218 @@
+use crate::chunks::ChunkMap;
+use crate::properties::{SpeciesProperties, IGNITION_NEVER};
+use crate::{
+    cell_idx, in_bounds, rand, rand_ra, set_cell_raw, CELL_CLOCK, CELL_STRIDE, CLOCK_PARITY_MASK, SPECIES_BATTERY,
+    SPECIES_HYDROGEN, SPECIES_MERCURY, SPECIES_METAL, SPECIES_OXYGEN, SPECIES_SWITCH, SPECIES_WATER,
+};
+
+/// Set in `ra` of a metal or switch cell that carried current this tick.
+/// Color noise (`rand_ra`) stays well below it.
+pub(crate) const CHARGED: u8 = 0x80;
+/// Set in `ra` of a switch that is closed and lets current through.
+pub(crate) const SWITCH_CLOSED: u8 = 0x01;
+
+/// Heat a live wire with at most two conducting neighbors picks up per
+/// tick, and the temperature it stops heating at.
+const WIRE_HEAT: u8 = 2;
+const WIRE_MAX_TEMP: u8 = 70;
+
+/// Chance per tick that water touching a live wire splits, and the share
+/// of it that comes off as hydrogen rather than oxygen: two to one, as in
+/// the water itself.
+const ELECTROLYSIS_CHANCE: f64 = 0.02;
+const HYDROGEN_SHARE: f64 = 2.0 / 3.0;
+
+const NEIGHBORS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+#[inline(always)]
+pub(crate) fn is_charged(ra: u8) -> bool {
+    ra & CHARGED != 0
+}
+
+/// Whether a cell passes current on: metal, mercury, batteries and closed
+/// switches.
+#[inline(always)]
+fn conducts(species: u8, ra: u8) -> bool {
+    match species {
+        SPECIES_METAL | SPECIES_MERCURY | SPECIES_BATTERY => true,
+        SPECIES_SWITCH => ra & SWITCH_CLOSED != 0,
+        _ => false,
+    }
+}
+
+/// One tick of current: floods outward from every battery through
+/// connected metal and closed switches, marking them charged, then lets
+/// live metal heat up where it's thin, spark flammables and split water.
+/// Runs between heat conduction and phase transitions, so sparked oil and
+/// gunpowder ignite in the same tick. Returns whether there was a battery
+/// to run current from.
+pub(crate) fn conduct(
+    cells: &mut [u8], width: usize, height: usize, props: &SpeciesProperties, chunks: &mut ChunkMap,
+) -> bool {
+    let mut frontier = Vec::new();
+    for (cell, c) in cells.chunks_exact_mut(CELL_STRIDE).enumerate() {
+        match c[0] {
+            SPECIES_METAL | SPECIES_MERCURY | SPECIES_SWITCH => c[1] &= !CHARGED,
+            SPECIES_BATTERY => frontier.push(cell),
+            _ => {}
+        }
+    }
+    if frontier.is_empty() { return false; }
+
+    while let Some(cell) = frontier.pop() {
+        let (x, y) = (cell % width, cell / width);
+        for &(dx, dy) in &NEIGHBORS {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !in_bounds(width, height, nx, ny) { continue; }
+            let ni = cell_idx(width, nx as usize, ny as usize);
+            let species = cells[ni];
+            if species == SPECIES_BATTERY || !conducts(species, cells[ni + 1]) || is_charged(cells[ni + 1]) {
+                continue;
+            }
+            cells[ni + 1] |= CHARGED;
+            frontier.push(ni / CELL_STRIDE);
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = cell_idx(width, x, y);
+            if cells[i] != SPECIES_METAL || !is_charged(cells[i + 1]) { continue; }
+            let mut links = 0;
+            for &(dx, dy) in &NEIGHBORS {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !in_bounds(width, height, nx, ny) { continue; }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let ni = cell_idx(width, nx, ny);
+                match cells[ni] {
+                    s if conducts(s, cells[ni + 1]) => links += 1,
+                    SPECIES_WATER => {
+                        if rand() < ELECTROLYSIS_CHANCE {
+                            let clock = cells[ni + CELL_CLOCK] & CLOCK_PARITY_MASK;
+                            let gas = if rand() < HYDROGEN_SHARE { SPECIES_HYDROGEN } else { SPECIES_OXYGEN };
+                            let temp = cells[ni + 2];
+                            set_cell_raw(cells, width, nx, ny, gas, rand_ra(), temp, clock);
+                            chunks.wake_area(nx, ny, 1);
+                        }
+                    }
+                    s => {
+                        let ignite = props.get(s).ignition_temp;
+                        if ignite != IGNITION_NEVER { spark(cells, ni, ignite); }
+                    }
+                }
+            }
+            if links <= 2 && cells[i + 2] < WIRE_MAX_TEMP {
+                cells[i + 2] = (cells[i + 2] + WIRE_HEAT).min(WIRE_MAX_TEMP);
+            }
+        }
+    }
+    true
+}
+
+/// Whether a battery sits in a chunk that is awake this tick. A battery
+/// only turns up through an edit or an update, and both wake its chunk,
+/// so with no current running last tick this finds any new one without
+/// sweeping the whole grid.
+pub(crate) fn battery_awake(cells: &[u8], width: usize, height: usize, chunks: &ChunkMap) -> bool {
+    (0..height).any(|y| {
+        chunks.active_spans(y, width).any(|(x0, x1)| {
+            (x0..x1).any(|x| cells[cell_idx(width, x, y)] == SPECIES_BATTERY)
+        })
+    })
+}
+
+/// Brings a flammable cell up to its ignition point.
+fn spark(cells: &mut [u8], i: usize, ignite: u8) {
+    cells[i + 2] = cells[i + 2].max(ignite);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_EMPTY, SPECIES_OIL, TEMP_AMBIENT, TEMP_OIL_IGNITE};
+
+    fn grid(width: usize, height: usize) -> (Vec<u8>, ChunkMap) {
+        (vec![0u8; width * height * CELL_STRIDE], ChunkMap::new(width, height))
+    }
+
+    fn ra(cells: &[u8], width: usize, x: usize, y: usize) -> u8 {
+        cells[cell_idx(width, x, y) + 1]
+    }
+
+    #[test]
+    fn current_stops_at_open_switch() {
+        let (mut cells, mut chunks) = grid(6, 1);
+        set_cell_raw(&mut cells, 6, 0, 0, SPECIES_BATTERY, 0, TEMP_AMBIENT, 0);
+        set_cell_raw(&mut cells, 6, 1, 0, SPECIES_METAL, 0, TEMP_AMBIENT, 0);
+        set_cell_raw(&mut cells, 6, 2, 0, SPECIES_SWITCH, 0, TEMP_AMBIENT, 0);
+        set_cell_raw(&mut cells, 6, 3, 0, SPECIES_METAL, 0, TEMP_AMBIENT, 0);
+        conduct(&mut cells, 6, 1, &SpeciesProperties::default(), &mut chunks);
+        assert!(is_charged(ra(&cells, 6, 1, 0)));
+        assert!(!is_charged(ra(&cells, 6, 3, 0)), "Open switch blocks current");
+
+        cells[cell_idx(6, 2, 0) + 1] |= SWITCH_CLOSED;
+        conduct(&mut cells, 6, 1, &SpeciesProperties::default(), &mut chunks);
+        assert!(is_charged(ra(&cells, 6, 3, 0)));
+
+        cells[cell_idx(6, 0, 0)] = SPECIES_EMPTY;
+        conduct(&mut cells, 6, 1, &SpeciesProperties::default(), &mut chunks);
+        assert!(!is_charged(ra(&cells, 6, 1, 0)), "Charge clears once the battery is gone");
+    }
+
+    #[test]
+    fn battery_awake_looks_only_in_active_chunks() {
+        let (mut cells, mut chunks) = grid(64, 32);
+        set_cell_raw(&mut cells, 64, 40, 10, SPECIES_BATTERY, 0, TEMP_AMBIENT, 0);
+        assert!(battery_awake(&cells, 64, 32, &chunks));
+        chunks.begin_tick();
+        chunks.begin_tick();
+        assert!(!battery_awake(&cells, 64, 32, &chunks), "Every chunk is asleep");
+        chunks.wake_area(5, 5, 0);
+        chunks.begin_tick();
+        assert!(!battery_awake(&cells, 64, 32, &chunks), "The battery's chunk is still asleep");
+        chunks.wake_area(40, 10, 0);
+        chunks.begin_tick();
+        assert!(battery_awake(&cells, 64, 32, &chunks));
+    }
+
+    #[test]
+    fn thin_wire_heats_and_sparks_oil() {
+        let (mut cells, mut chunks) = grid(4, 2);
+        set_cell_raw(&mut cells, 4, 0, 0, SPECIES_BATTERY, 0, TEMP_AMBIENT, 0);
+        set_cell_raw(&mut cells, 4, 1, 0, SPECIES_METAL, 0, TEMP_AMBIENT, 0);
+        set_cell_raw(&mut cells, 4, 1, 1, SPECIES_OIL, 0, TEMP_AMBIENT, 0);
+        conduct(&mut cells, 4, 2, &SpeciesProperties::default(), &mut chunks);
+        assert_eq!(cells[cell_idx(4, 1, 0) + 2], TEMP_AMBIENT + WIRE_HEAT);
+        assert_eq!(cells[cell_idx(4, 1, 1) + 2], TEMP_OIL_IGNITE);
+    }
+
+    #[test]
+    fn thick_metal_stays_cool() {
+        let (mut cells, mut chunks) = grid(3, 3);
+        for y in 0..3 {
+            for x in 0..3 { set_cell_raw(&mut cells, 3, x, y, SPECIES_METAL, 0, TEMP_AMBIENT, 0); }
+        }
+        set_cell_raw(&mut cells, 3, 0, 0, SPECIES_BATTERY, 0, TEMP_AMBIENT, 0);
+        conduct(&mut cells, 3, 3, &SpeciesProperties::default(), &mut chunks);
+        assert!(is_charged(ra(&cells, 3, 1, 1)));
+        assert_eq!(cells[cell_idx(3, 1, 1) + 2], TEMP_AMBIENT, "Current spreads out in a thick block");
+    }
+
+    #[test]
+    fn live_wire_splits_water_two_to_one() {
+        let (width, height) = (2, 300);
+        let (mut cells, mut chunks) = grid(width, height);
+        for y in 0..height {
+            set_cell_raw(&mut cells, width, 0, y, SPECIES_METAL, 0, TEMP_AMBIENT, 0);
+            set_cell_raw(&mut cells, width, 1, y, SPECIES_WATER, 0, TEMP_AMBIENT, 0);
+        }
+        set_cell_raw(&mut cells, width, 0, 0, SPECIES_BATTERY, 0, TEMP_AMBIENT, 0);
+        for _ in 0..100 { conduct(&mut cells, width, height, &SpeciesProperties::default(), &mut chunks); }
+        let count = |s: u8| cells.chunks_exact(CELL_STRIDE).filter(|c| c[0] == s).count();
+        let (hydrogen, oxygen) = (count(SPECIES_HYDROGEN), count(SPECIES_OXYGEN));
+        assert!(oxygen > 0 && hydrogen > oxygen, "{hydrogen} hydrogen to {oxygen} oxygen");
+        assert_eq!(hydrogen + oxygen + count(SPECIES_WATER), height, "Nothing else comes of it");
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+/// Events kept for `World::drain_events` before the oldest are dropped.
+pub(crate) const EVENT_HISTORY: usize = 4096;
+
+/// `u32`s per event in `World::drain_events_flat`.
+pub const EVENT_STRIDE: usize = 5;
+
+/// Something noteworthy that happened during a tick, for frontends to hang
+/// sounds and effects on. Positions are where it happened; species are the
+/// cell's species before the change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A fuel cell caught fire.
+    Ignited { x: usize, y: usize, from: u8 },
+    /// Water boiled off, out of saltwater, mud or wet sand too.
+    Boiled { x: usize, y: usize, from: u8 },
+    /// Steam condensed back to water.
+    Condensed { x: usize, y: usize },
+    /// Ice, snow or stone melted, or sand fused into glass.
+    Melted { x: usize, y: usize, from: u8 },
+    /// A liquid froze or set solid.
+    Froze { x: usize, y: usize, from: u8 },
+    /// `species` was dissolved by `by`: salt or soap in water, oil lifted
+    /// by soap, anything in acid.
+    Dissolved { x: usize, y: usize, species: u8, by: u8 },
+    /// A blast of `radius` cells went off, centered here.
+    Exploded { x: usize, y: usize, radius: u8 },
+    /// Water put out the fire here, or it ran out of air under the oxygen
+    /// model.
+    Extinguished { x: usize, y: usize },
+    /// A data-driven reaction rule turned `species` here, touching `with`.
+    Reacted { x: usize, y: usize, species: u8, with: u8 },
+    /// Lightning struck a `species` cell here.
+    Struck { x: usize, y: usize, species: u8 },
+    /// Ice or stone cracked apart from a sudden change in temperature.
+    Cracked { x: usize, y: usize, from: u8 },
+}
+
+impl Event {
+    /// The event as `EVENT_STRIDE` numbers: `[kind, x, y, a, b]`, where kind
+    /// is 0 Ignited, 1 Boiled, 2 Condensed, 3 Melted, 4 Froze, 5 Dissolved,
+    /// 6 Exploded, 7 Extinguished, 8 Reacted, 9 Struck, 10 Cracked. `a` is
+    /// the `from` species, the dissolved, reacting or struck species or the
+    /// blast radius; `b` is the dissolving species or the rule's agent.
+    /// Unused fields are 0.
+    pub fn flat(&self) -> [u32; EVENT_STRIDE] {
+        let (kind, x, y, a, b) = match *self {
+            Event::Ignited { x, y, from } => (0, x, y, from, 0),
+            Event::Boiled { x, y, from } => (1, x, y, from, 0),
+            Event::Condensed { x, y } => (2, x, y, 0, 0),
+            Event::Melted { x, y, from } => (3, x, y, from, 0),
+            Event::Froze { x, y, from } => (4, x, y, from, 0),
+            Event::Dissolved { x, y, species, by } => (5, x, y, species, by),
+            Event::Exploded { x, y, radius } => (6, x, y, radius, 0),
+            Event::Extinguished { x, y } => (7, x, y, 0, 0),
+            Event::Reacted { x, y, species, with } => (8, x, y, species, with),
+            Event::Struck { x, y, species } => (9, x, y, species, 0),
+            Event::Cracked { x, y, from } => (10, x, y, from, 0),
+        };
+        [kind, x as u32, y as u32, a as u32, b as u32]
+    }
+}
+
+/// Where a tick's updates report their events. A sink that's off drops
+/// them on the spot, so a world nobody listens to doesn't pay for events.
+#[derive(Debug, Default)]
+pub(crate) struct EventSink {
+    events: Vec<Event>,
+    on: bool,
+}
+
+impl EventSink {
+    pub(crate) fn new(on: bool) -> EventSink {
+        EventSink { events: Vec::new(), on }
+    }
+
+    #[inline]
+    pub(crate) fn is_on(&self) -> bool { self.on }
+
+    #[inline]
+    pub(crate) fn push(&mut self, event: Event) {
+        if self.on { self.events.push(event); }
+    }
+
+    /// Moves `other`'s events onto the end of this one's.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn append(&mut self, other: &mut EventSink) {
+        self.events.append(&mut other.events);
+    }
+}
+
+impl Deref for EventSink {
+    type Target = [Event];
+
+    fn deref(&self) -> &[Event] { &self.events }
+}
+
+/// Bounded history of events waiting to be drained. Full history drops
+/// the oldest events first.
+pub(crate) struct EventLog {
+    events: VecDeque<Event>,
+}
+
+impl EventLog {
+    pub(crate) fn new() -> EventLog {
+        EventLog { events: VecDeque::new() }
+    }
+
+    /// Appends one tick's events.
+    pub(crate) fn extend(&mut self, tick: &mut EventSink) {
+        let tick = &mut tick.events;
+        let overflow = (self.events.len() + tick.len()).saturating_sub(EVENT_HISTORY);
+        self.events.drain(..overflow.min(self.events.len()));
+        let skip = tick.len().saturating_sub(EVENT_HISTORY);
+        self.events.extend(tick.drain(..).skip(skip));
+    }
+
+    pub(crate) fn drain(&mut self) -> Vec<Event> {
+        self.events.drain(..).collect()
+    }
+
+    pub(crate) fn clear(&mut self) { self.events.clear(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_the_newest_events() {
+        let mut log = EventLog::new();
+        let mut tick = EventSink::new(true);
+        for x in 0..EVENT_HISTORY - 1 { tick.push(Event::Condensed { x, y: 0 }); }
+        log.extend(&mut tick);
+        assert!(tick.is_empty());
+        for y in 0..3 { tick.push(Event::Condensed { x: 0, y }); }
+        log.extend(&mut tick);
+
+        let events = log.drain();
+        assert_eq!(events.len(), EVENT_HISTORY);
+        assert_eq!(events[0], Event::Condensed { x: 2, y: 0 });
+        assert_eq!(events.last(), Some(&Event::Condensed { x: 0, y: 2 }));
+        assert!(log.drain().is_empty());
+    }
+
+    #[test]
+    fn a_sink_that_is_off_keeps_nothing() {
+        let mut tick = EventSink::default();
+        tick.push(Event::Condensed { x: 0, y: 0 });
+        assert!(tick.is_empty());
+    }
+
+    #[test]
+    fn flat_layout() {
+        assert_eq!(Event::Dissolved { x: 3, y: 4, species: 17, by: 2 }.flat(), [5, 3, 4, 17, 2]);
+        assert_eq!(Event::Exploded { x: 1, y: 2, radius: 6 }.flat(), [6, 1, 2, 6, 0]);
+    }
+}
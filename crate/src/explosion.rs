@@ -0,0 +1,243 @@
+use crate::chunks::ChunkMap;
+use crate::events::{Event, EventSink};
+use crate::{
+    cell_idx, get_species, in_bounds, is_pinned, rand, rand_ra, rand_range, set_cell_raw, set_clock, set_fall_speed,
+    set_fire_origin, swap_cells, SPECIES_ACID, SPECIES_ANT, SPECIES_ASH, SPECIES_DIRT, SPECIES_EMPTY, SPECIES_FIRE,
+    SPECIES_FISH, SPECIES_FOAM, SPECIES_GAS, SPECIES_GRAVEL, SPECIES_HYDROGEN, SPECIES_GUNPOWDER, SPECIES_LAVA, SPECIES_LEAD,
+    SPECIES_MERCURY, SPECIES_MOLTEN_WAX, SPECIES_MUD, SPECIES_NITRO, SPECIES_OIL, SPECIES_SALT, SPECIES_SALTWATER,
+    SPECIES_SAND, SPECIES_SEED, SPECIES_SMOKE, SPECIES_SNOW, SPECIES_STONE, SPECIES_THERMITE, SPECIES_URANIUM,
+    SPECIES_WALL, SPECIES_WATER, SPECIES_WET_SAND,
+};
+
+// Fuel for the short-lived fire left in a blast core.
+const BLAST_FUEL_MIN: u8 = 10;
+const BLAST_FUEL_MAX: u8 = 25;
+
+// A detonating gas pocket flashes to short-lived fire at this heat.
+const GAS_FLASH_HEAT: u8 = 200;
+const GAS_FLASH_FUEL_MIN: u8 = 4;
+const GAS_FLASH_FUEL_MAX: u8 = 12;
+// The pocket's pressure wave grows with the square root of its size.
+const GAS_BLAST_SCALE: f32 = 1.5;
+const GAS_BLAST_MAX_RADIUS: usize = 14;
+
+/// Furthest a loose particle at the blast rim can be thrown, in cells.
+pub(crate) const MAX_FLING: usize = 6;
+
+/// Particles light enough for a blast to throw around.
+#[inline(always)]
+fn is_loose(species: u8) -> bool {
+    matches!(
+        species,
+        SPECIES_SAND | SPECIES_WATER | SPECIES_OIL | SPECIES_ACID | SPECIES_LAVA | SPECIES_STONE | SPECIES_ASH
+            | SPECIES_GUNPOWDER | SPECIES_SALT | SPECIES_SALTWATER | SPECIES_SEED | SPECIES_SNOW | SPECIES_MERCURY
+            | SPECIES_THERMITE | SPECIES_MOLTEN_WAX | SPECIES_DIRT | SPECIES_MUD | SPECIES_ANT | SPECIES_FISH
+            | SPECIES_NITRO | SPECIES_GRAVEL | SPECIES_WET_SAND | SPECIES_FOAM | SPECIES_URANIUM | SPECIES_LEAD
+    )
+}
+
+/// Blasts a circle of `radius` cells around (cx, cy). The inner half turns
+/// to fire, the outer half heats up and fills with smoke, and loose
+/// particles out to twice the radius are flung away from the center.
+/// `power` is the heat added at the center, fading linearly to the rim.
+/// Walls and pinned cells are never affected.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn explode(
+    cells: &mut [u8], width: usize, height: usize,
+    cx: usize, cy: usize, radius: usize, power: u8, clock: u8,
+    chunks: &mut ChunkMap, events: &mut EventSink,
+) {
+    events.push(Event::Exploded { x: cx, y: cy, radius: radius.min(u8::MAX as usize) as u8 });
+    let r = radius.max(1) as f32;
+    let reach = radius.max(1) * 2;
+    let mut flung: Vec<(usize, usize, f32)> = Vec::new();
+
+    for y in cy.saturating_sub(reach)..=(cy + reach).min(height - 1) {
+        for x in cx.saturating_sub(reach)..=(cx + reach).min(width - 1) {
+            let dx = x as f32 - cx as f32;
+            let dy = y as f32 - cy as f32;
+            let d = (dx * dx + dy * dy).sqrt();
+            if d > reach as f32 { continue; }
+            let i = cell_idx(width, x, y);
+            let species = cells[i];
+            if species == SPECIES_WALL || is_pinned(cells, i) { continue; }
+
+            if d <= r * 0.5 {
+                let temp = cells[i + 2].max(power);
+                set_cell_raw(cells, width, x, y, SPECIES_FIRE, rand_range(BLAST_FUEL_MIN, BLAST_FUEL_MAX), temp, clock);
+                continue;
+            }
+            if d <= r {
+                let falloff = 1.0 - d / r;
+                cells[i + 2] = cells[i + 2].saturating_add((power as f32 * falloff) as u8);
+                if species == SPECIES_EMPTY && rand() < falloff as f64 * 2.0 {
+                    let temp = cells[i + 2];
+                    set_cell_raw(cells, width, x, y, SPECIES_SMOKE, rand_ra(), temp, clock);
+                    continue;
+                }
+            }
+            if is_loose(species) {
+                flung.push((x, y, d));
+            }
+        }
+    }
+
+    // Outermost first, so inner particles have somewhere to land.
+    flung.sort_by(|a, b| b.2.total_cmp(&a.2));
+    for (x, y, d) in flung {
+        let strength = (1.0 - (d - r * 0.5) / (reach as f32 - r * 0.5)).clamp(0.0, 1.0);
+        let distance = (strength * MAX_FLING as f32).round() as usize;
+        fling(cells, width, height, x, y, cx, cy, distance, clock);
+    }
+
+    chunks.wake_area(cx, cy, reach + MAX_FLING);
+}
+
+/// Primes a gas or hydrogen cell a flame has touched, heating it to its
+/// flash point so `phase_transitions` sets its pocket off at the start of
+/// the next tick. The flood fill and the blast can reach any distance, so
+/// they stay out of the movement pass, where an update may only touch
+/// cells within `parallel::MAX_REACH` of itself.
+pub(crate) fn prime_gas(cells: &mut [u8], width: usize, x: usize, y: usize, chunks: &mut ChunkMap) {
+    let i = cell_idx(width, x, y);
+    cells[i + 2] = cells[i + 2].max(GAS_FLASH_HEAT);
+    chunks.wake_area(x, y, 0);
+}
+
+/// Sets off the whole pocket of gas or hydrogen connected (8-way) to
+/// (x, y) at once: every cell of it flashes to hot fire, then a blast sized
+/// to the pocket goes off at its center.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn detonate_gas(
+    cells: &mut [u8], width: usize, height: usize,
+    x: usize, y: usize, clock: u8, chunks: &mut ChunkMap, events: &mut EventSink,
+) {
+    let fuel = get_species(cells, width, x, y);
+    if !matches!(fuel, SPECIES_GAS | SPECIES_HYDROGEN) { return; }
+    let mut stack = vec![(x, y)];
+    let (mut count, mut sum_x, mut sum_y) = (0usize, 0usize, 0usize);
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (x, y, x, y);
+    ignite_gas(cells, width, x, y, fuel, clock);
+
+    while let Some((x, y)) = stack.pop() {
+        count += 1;
+        sum_x += x;
+        sum_y += y;
+        (min_x, min_y, max_x, max_y) = (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y));
+        for dy in -1isize..=1 {
+            for dx in -1isize..=1 {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !in_bounds(width, height, nx, ny) { continue; }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if get_species(cells, width, nx, ny) != fuel { continue; }
+                // Igniting marks the cell as visited.
+                ignite_gas(cells, width, nx, ny, fuel, clock);
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    let radius = ((count as f32).sqrt() * GAS_BLAST_SCALE).round() as usize;
+    let (cx, cy) = (sum_x / count, sum_y / count);
+    explode(cells, width, height, cx, cy, radius.clamp(2, GAS_BLAST_MAX_RADIUS), GAS_FLASH_HEAT, clock, chunks, events);
+    chunks.wake_area((min_x + max_x) / 2, (min_y + max_y) / 2, (max_x - min_x).max(max_y - min_y) / 2 + 1);
+}
+
+fn ignite_gas(cells: &mut [u8], width: usize, x: usize, y: usize, fuel: u8, clock: u8) {
+    let i = cell_idx(width, x, y);
+    let temp = cells[i + 2].max(GAS_FLASH_HEAT);
+    set_cell_raw(cells, width, x, y, SPECIES_FIRE, rand_range(GAS_FLASH_FUEL_MIN, GAS_FLASH_FUEL_MAX), temp, clock);
+    set_fire_origin(cells, i, fuel);
+}
+
+/// Moves the particle at (x, y) up to `distance` cells directly away from
+/// (cx, cy), one cell at a time, stopping at the first non-empty cell.
+#[allow(clippy::too_many_arguments)]
+fn fling(
+    cells: &mut [u8], width: usize, height: usize,
+    x: usize, y: usize, cx: usize, cy: usize, distance: usize, clock: u8,
+) {
+    if distance == 0 || get_species(cells, width, x, y) == SPECIES_EMPTY { return; }
+    let dx = x as f32 - cx as f32;
+    let dy = y as f32 - cy as f32;
+    let len = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+    let (ux, uy) = (dx / len, dy / len);
+    let (mut px, mut py) = (x, y);
+    for step in 1..=distance {
+        let nx = (x as f32 + ux * step as f32).round() as isize;
+        let ny = (y as f32 + uy * step as f32).round() as isize;
+        if (nx, ny) == (px as isize, py as isize) { continue; }
+        if !in_bounds(width, height, nx, ny) { break; }
+        let (nx, ny) = (nx as usize, ny as usize);
+        if get_species(cells, width, nx, ny) != SPECIES_EMPTY { break; }
+        swap_cells(cells, width, px, py, nx, ny);
+        px = nx;
+        py = ny;
+    }
+    set_fall_speed(cells, cell_idx(width, px, py), 0);
+    set_clock(cells, width, px, py, clock);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(width: usize, height: usize) -> (Vec<u8>, ChunkMap) {
+        (vec![0; width * height * crate::CELL_STRIDE], ChunkMap::new(width, height))
+    }
+
+    #[test]
+    fn gas_detonates_as_one_pocket() {
+        let (mut cells, mut chunks) = grid(40, 10);
+        // Two diagonal-linked runs of gas, and a separate pocket far away.
+        for x in 2..8 { set_cell_raw(&mut cells, 40, x, 1, SPECIES_GAS, 0, 12, 0); }
+        for x in 8..14 { set_cell_raw(&mut cells, 40, x, 2, SPECIES_GAS, 0, 12, 0); }
+        for x in 34..38 { set_cell_raw(&mut cells, 40, x, 8, SPECIES_GAS, 0, 12, 0); }
+        let mut events = EventSink::new(true);
+        detonate_gas(&mut cells, 40, 10, 2, 1, 1, &mut chunks, &mut events);
+        assert!(matches!(events[..], [Event::Exploded { .. }]), "One blast for the whole pocket: {events:?}");
+        for x in 2..14 {
+            let y = if x < 8 { 1 } else { 2 };
+            assert_ne!(get_species(&cells, 40, x, y), SPECIES_GAS, "({x}, {y}) should have gone up");
+        }
+        for x in 34..38 { assert_eq!(get_species(&cells, 40, x, 8), SPECIES_GAS); }
+    }
+
+    #[test]
+    fn core_turns_to_fire_but_walls_survive() {
+        let (mut cells, mut chunks) = grid(21, 21);
+        set_cell_raw(&mut cells, 21, 10, 10, SPECIES_SAND, 0, 12, 0);
+        set_cell_raw(&mut cells, 21, 11, 10, SPECIES_WALL, 0, 0, 0);
+        explode(&mut cells, 21, 21, 10, 10, 6, 150, 1, &mut chunks, &mut EventSink::default());
+        assert_eq!(get_species(&cells, 21, 10, 10), SPECIES_FIRE);
+        assert_eq!(get_species(&cells, 21, 11, 10), SPECIES_WALL);
+        assert!(cells[cell_idx(21, 10, 10) + 2] >= 150);
+    }
+
+    #[test]
+    fn loose_particles_are_flung_outward() {
+        let (mut cells, mut chunks) = grid(41, 5);
+        set_cell_raw(&mut cells, 41, 25, 2, SPECIES_SAND, 0, 12, 0);
+        explode(&mut cells, 41, 5, 20, 2, 4, 100, 1, &mut chunks, &mut EventSink::default());
+        let sand: Vec<usize> = (0..41).filter(|&x| get_species(&cells, 41, x, 2) == SPECIES_SAND).collect();
+        assert_eq!(sand.len(), 1);
+        assert!(sand[0] > 25, "Sand should move away from the blast, now at {}", sand[0]);
+    }
+
+    #[test]
+    fn fling_stops_at_obstacles() {
+        let (mut cells, _) = grid(20, 1);
+        set_cell_raw(&mut cells, 20, 5, 0, SPECIES_SAND, 0, 12, 0);
+        set_cell_raw(&mut cells, 20, 8, 0, SPECIES_WALL, 0, 0, 0);
+        fling(&mut cells, 20, 1, 5, 0, 0, 0, 6, 1);
+        assert_eq!(get_species(&cells, 20, 7, 0), SPECIES_SAND);
+    }
+
+    #[test]
+    fn explosion_near_edge_does_not_panic() {
+        let (mut cells, mut chunks) = grid(8, 8);
+        explode(&mut cells, 8, 8, 0, 7, 10, 200, 1, &mut chunks, &mut EventSink::default());
+        assert_eq!(get_species(&cells, 8, 0, 7), SPECIES_FIRE);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::chunks::ChunkMap;
+
+/// Side length in cells of one force-grid cell.
+pub(crate) const FORCE_CELL: usize = 4;
+
+/// Default per-tick multiplier applied to every painted vector.
+pub(crate) const FORCE_DECAY_DEFAULT: f32 = 0.98;
+
+/// Vectors weaker than this on both axes are snapped to zero.
+const FORCE_EPSILON: f32 = 0.01;
+
+/// Coarse vector field painted by the user. Each component is the chance
+/// per tick (-1.0..=1.0, sign = direction) that a fluid in that area is
+/// pushed one cell along that axis.
+pub(crate) struct ForceField {
+    cols: usize,
+    rows: usize,
+    fx: Box<[f32]>,
+    fy: Box<[f32]>,
+    pub(crate) decay: f32,
+}
+
+impl ForceField {
+    pub(crate) fn new(width: usize, height: usize) -> ForceField {
+        let cols = width.div_ceil(FORCE_CELL);
+        let rows = height.div_ceil(FORCE_CELL);
+        ForceField {
+            cols,
+            rows,
+            fx: vec![0.0; cols * rows].into_boxed_slice(),
+            fy: vec![0.0; cols * rows].into_boxed_slice(),
+            decay: FORCE_DECAY_DEFAULT,
+        }
+    }
+
+    pub(crate) fn with_decay(width: usize, height: usize, decay: f32) -> ForceField {
+        ForceField { decay, ..ForceField::new(width, height) }
+    }
+
+    /// Adds (dx, dy) to every force cell whose center lies within `radius`
+    /// cells of (x, y), fading linearly toward the rim.
+    pub(crate) fn paint(&mut self, x: usize, y: usize, dx: f32, dy: f32, radius: usize) {
+        let r = radius.max(1) as f32;
+        let cx0 = x.saturating_sub(radius) / FORCE_CELL;
+        let cy0 = y.saturating_sub(radius) / FORCE_CELL;
+        let cx1 = ((x + radius) / FORCE_CELL).min(self.cols.saturating_sub(1));
+        let cy1 = ((y + radius) / FORCE_CELL).min(self.rows.saturating_sub(1));
+        for cy in cy0..=cy1 {
+            for cx in cx0..=cx1 {
+                let px = (cx * FORCE_CELL + FORCE_CELL / 2) as f32 - x as f32;
+                let py = (cy * FORCE_CELL + FORCE_CELL / 2) as f32 - y as f32;
+                let dist = (px * px + py * py).sqrt();
+                if dist > r + FORCE_CELL as f32 / 2.0 { continue; }
+                let falloff = (1.0 - dist / (r + FORCE_CELL as f32)).clamp(0.0, 1.0);
+                let i = cy * self.cols + cx;
+                self.fx[i] = (self.fx[i] + dx * falloff).clamp(-1.0, 1.0);
+                self.fy[i] = (self.fy[i] + dy * falloff).clamp(-1.0, 1.0);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn at(&self, x: usize, y: usize) -> (f32, f32) {
+        let i = (y / FORCE_CELL) * self.cols + x / FORCE_CELL;
+        (self.fx[i], self.fy[i])
+    }
+
+    /// Fades every vector and wakes the chunks under the ones still alive.
+    /// Returns false once the whole field has decayed to zero.
+    pub(crate) fn decay_and_wake(&mut self, chunks: &mut ChunkMap) -> bool {
+        let mut any = false;
+        for i in 0..self.fx.len() {
+            let (mut fx, mut fy) = (self.fx[i] * self.decay, self.fy[i] * self.decay);
+            if fx.abs() < FORCE_EPSILON && fy.abs() < FORCE_EPSILON {
+                fx = 0.0;
+                fy = 0.0;
+            } else {
+                any = true;
+                let x = (i % self.cols) * FORCE_CELL + FORCE_CELL / 2;
+                let y = (i / self.cols) * FORCE_CELL + FORCE_CELL / 2;
+                chunks.wake_area(x, y, FORCE_CELL / 2);
+            }
+            self.fx[i] = fx;
+            self.fy[i] = fy;
+        }
+        any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_fades_toward_rim() {
+        let mut f = ForceField::new(64, 64);
+        f.paint(32, 32, 1.0, 0.0, 12);
+        let (center, _) = f.at(32, 32);
+        let (rim, _) = f.at(42, 32);
+        let (outside, _) = f.at(60, 32);
+        assert!(center > rim && rim > 0.0, "center={} rim={}", center, rim);
+        assert_eq!(outside, 0.0);
+    }
+
+    #[test]
+    fn paint_clamps_accumulated_force() {
+        let mut f = ForceField::new(16, 16);
+        for _ in 0..10 { f.paint(8, 8, 0.0, -1.0, 4); }
+        assert_eq!(f.at(8, 8), (0.0, -1.0));
+    }
+
+    #[test]
+    fn decay_eventually_clears_field() {
+        let mut f = ForceField::new(16, 16);
+        let mut chunks = ChunkMap::new(16, 16);
+        f.paint(8, 8, 1.0, 1.0, 4);
+        f.decay = 0.5;
+        let mut ticks = 0;
+        while f.decay_and_wake(&mut chunks) { ticks += 1; }
+        assert!(ticks < 10);
+        assert_eq!(f.at(8, 8), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+
+use crate::rle::{read_varint, write_varint};
+use crate::CELL_STRIDE;
+
+/// Bytes of compressed snapshots kept for undo and redo until
+/// `World::set_history_limit` says otherwise.
+pub(crate) const DEFAULT_HISTORY_BYTES: usize = 16 << 20;
+
+/// One saved grid. Unlike an RLE scene it keeps every byte of every cell,
+/// so fuel, fall speed and the rest of the per-cell state come back
+/// exactly. Each byte of the cell layout is stored as its own plane of
+/// `{ length:varint value:u8 }` runs: species and temperature collapse
+/// into long runs even where the color noise in `ra` doesn't.
+pub(crate) struct Snapshot {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    data: Vec<u8>,
+}
+
+impl Snapshot {
+    pub(crate) fn capture(cells: &[u8], width: usize, height: usize) -> Snapshot {
+        let mut data = Vec::new();
+        for plane in 0..CELL_STRIDE {
+            let mut bytes = cells.iter().skip(plane).step_by(CELL_STRIDE).copied();
+            let Some(mut run) = bytes.next() else { break; };
+            let mut len = 1;
+            for b in bytes {
+                if b == run {
+                    len += 1;
+                    continue;
+                }
+                write_varint(&mut data, len);
+                data.push(run);
+                run = b;
+                len = 1;
+            }
+            write_varint(&mut data, len);
+            data.push(run);
+        }
+        data.shrink_to_fit();
+        Snapshot { width, height, data }
+    }
+
+    pub(crate) fn cells(&self) -> Vec<u8> {
+        let mut cells = vec![0; self.width * self.height * CELL_STRIDE];
+        let mut rest = self.data.as_slice();
+        for plane in 0..CELL_STRIDE {
+            let mut i = plane;
+            while i < cells.len() {
+                // Written by `capture`, so always well formed.
+                let len = read_varint(&mut rest).expect("corrupt snapshot");
+                let (&value, tail) = rest.split_first().expect("corrupt snapshot");
+                rest = tail;
+                for _ in 0..len {
+                    cells[i] = value;
+                    i += CELL_STRIDE;
+                }
+            }
+        }
+        cells
+    }
+
+    /// Compressed size in bytes.
+    pub(crate) fn size(&self) -> usize { self.data.len() }
+}
+
+/// Undo and redo stacks of snapshots, sharing one byte budget. Over budget,
+/// the oldest undo steps go first, then the furthest redo steps.
+pub(crate) struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    limit: usize,
+    used: usize,
+}
+
+impl History {
+    pub(crate) fn new(limit: usize) -> History {
+        History { undo: VecDeque::new(), redo: Vec::new(), limit, used: 0 }
+    }
+
+    /// Saves a new undo step and forgets anything that could be redone.
+    pub(crate) fn push(&mut self, snapshot: Snapshot) {
+        for s in self.redo.drain(..) { self.used -= s.size(); }
+        self.used += snapshot.size();
+        self.undo.push_back(snapshot);
+        self.trim();
+    }
+
+    /// Swaps `current` for the newest undo step, keeping `current` for
+    /// redo. None, and `current` is dropped, when there's nothing to undo.
+    pub(crate) fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.used += current.size();
+        self.used -= previous.size();
+        self.redo.push(current);
+        self.trim();
+        Some(previous)
+    }
+
+    /// The reverse of `undo`.
+    pub(crate) fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.used += current.size();
+        self.used -= next.size();
+        self.undo.push_back(current);
+        self.trim();
+        Some(next)
+    }
+
+    pub(crate) fn can_undo(&self) -> bool { !self.undo.is_empty() }
+    pub(crate) fn can_redo(&self) -> bool { !self.redo.is_empty() }
+
+    pub(crate) fn used(&self) -> usize { self.used }
+
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.used > self.limit {
+            let dropped = match self.undo.pop_front() {
+                Some(s) => s,
+                None if !self.redo.is_empty() => self.redo.remove(0),
+                None => break,
+            };
+            self.used -= dropped.size();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(fill: u8) -> Vec<u8> {
+        (0..6 * 4 * CELL_STRIDE).map(|i| if i % CELL_STRIDE == 1 { (i * 37) as u8 } else { fill }).collect()
+    }
+
+    #[test]
+    fn snapshots_keep_every_byte() {
+        let cells = grid(3);
+        let snap = Snapshot::capture(&cells, 6, 4);
+        assert_eq!(snap.cells(), cells);
+        assert!(snap.size() < cells.len(), "Constant planes should collapse: {} bytes", snap.size());
+        assert!(Snapshot::capture(&[], 0, 0).cells().is_empty());
+    }
+
+    #[test]
+    fn budget_drops_the_oldest_steps() {
+        let size = Snapshot::capture(&grid(0), 6, 4).size();
+        let mut history = History::new(size * 2);
+        for fill in 0..3 { history.push(Snapshot::capture(&grid(fill), 6, 4)); }
+        assert_eq!(history.used(), size * 2);
+
+        let back = history.undo(Snapshot::capture(&grid(9), 6, 4)).unwrap();
+        assert_eq!(back.cells(), grid(2));
+        assert!(history.can_undo() && history.can_redo());
+        assert_eq!(history.redo(back).unwrap().cells(), grid(9));
+        assert!(!history.can_redo());
+        assert_eq!(history.used(), size * 2);
+
+        history.set_limit(0);
+        assert_eq!(history.used(), 0);
+        assert!(!history.can_undo());
+    }
+}
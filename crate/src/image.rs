@@ -0,0 +1,135 @@
+use crate::render::{PALETTE, RGBA_STRIDE};
+use crate::custom::is_species_id;
+use crate::SPECIES_EMPTY;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Largest squared RGB distance at which a pixel still matches a color in
+/// the default map: enough to absorb the renderer's per-cell shading.
+const DEFAULT_TOLERANCE: u32 = 40 * 40 * 3;
+
+/// Pixels less opaque than this load as empty space.
+const MIN_ALPHA: u8 = 128;
+
+/// Maps pixel colors to species for `World::load_from_image`. Each pixel
+/// takes the species of the nearest listed color, if it's within the
+/// tolerance; anything else, and any mostly transparent pixel, is empty.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct ColorMap {
+    entries: Vec<([u8; 3], u8)>,
+    tolerance: u32,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl ColorMap {
+    /// An empty map: every pixel loads as empty space until colors are added.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new() -> ColorMap {
+        ColorMap { entries: Vec::new(), tolerance: DEFAULT_TOLERANCE }
+    }
+
+    /// The renderer's base color for every species, so exported images
+    /// load back as the scene they show.
+    pub fn from_palette() -> ColorMap {
+        let mut map = ColorMap::new();
+        for (species, &rgb) in PALETTE.iter().enumerate() {
+            map.entries.push((rgb, species as u8));
+        }
+        map
+    }
+
+    /// Maps (r, g, b) to `species`, replacing any earlier entry for that
+    /// exact color. Ignores unknown species.
+    pub fn insert(&mut self, r: u8, g: u8, b: u8, species: u8) {
+        if !is_species_id(species) { return; }
+        let rgb = [r, g, b];
+        self.entries.retain(|&(c, _)| c != rgb);
+        self.entries.push((rgb, species));
+    }
+
+    /// Squared RGB distance within which a pixel matches an entry.
+    pub fn set_tolerance(&mut self, tolerance: u32) {
+        self.tolerance = tolerance;
+    }
+
+    pub(crate) fn species_for(&self, rgba: [u8; 4]) -> u8 {
+        if rgba[3] < MIN_ALPHA { return SPECIES_EMPTY; }
+        self.entries
+            .iter()
+            .map(|&(c, species)| {
+                let d: u32 = (0..3).map(|k| (c[k] as i32 - rgba[k] as i32).pow(2) as u32).sum();
+                (d, species)
+            })
+            .filter(|&(d, _)| d <= self.tolerance)
+            .min_by_key(|&(d, _)| d)
+            .map_or(SPECIES_EMPTY, |(_, species)| species)
+    }
+}
+
+impl Default for ColorMap {
+    fn default() -> Self { ColorMap::new() }
+}
+
+/// Decodes a PNG of any common color type into (width, height, RGBA8).
+pub(crate) fn decode_png(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>), String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    let pixels = &buf[..frame.buffer_size()];
+
+    let rgba = match frame.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => return Err("indexed PNG was not expanded".into()),
+    };
+    Ok((width, height, rgba))
+}
+
+/// Encodes tightly packed RGBA8 pixels as a PNG.
+pub(crate) fn encode_png(width: usize, height: usize, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    debug_assert_eq!(rgba.len(), width * height * RGBA_STRIDE);
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_SAND, SPECIES_WALL, SPECIES_WATER};
+
+    #[test]
+    fn nearest_color_within_tolerance_wins() {
+        let mut map = ColorMap::new();
+        map.insert(255, 0, 0, SPECIES_SAND);
+        map.insert(0, 0, 255, SPECIES_WATER);
+        map.set_tolerance(100 * 100);
+        assert_eq!(map.species_for([230, 20, 10, 255]), SPECIES_SAND);
+        assert_eq!(map.species_for([10, 10, 240, 255]), SPECIES_WATER);
+        assert_eq!(map.species_for([0, 255, 0, 255]), SPECIES_EMPTY, "Too far from everything");
+        assert_eq!(map.species_for([255, 0, 0, 10]), SPECIES_EMPTY, "Transparent");
+        map.insert(255, 0, 0, SPECIES_WALL);
+        assert_eq!(map.species_for([255, 0, 0, 255]), SPECIES_WALL);
+    }
+
+    #[test]
+    fn png_round_trips() {
+        let rgba: Vec<u8> = (0..3 * 2 * 4).map(|v| (v * 10) as u8).collect();
+        let png = encode_png(3, 2, &rgba).unwrap();
+        assert_eq!(decode_png(&png).unwrap(), (3, 2, rgba));
+        assert!(decode_png(b"not a png").is_err());
+    }
+}
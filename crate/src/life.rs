@@ -0,0 +1,189 @@
+use crate::chunks::ChunkMap;
+use crate::{cell_idx, CELL_CLOCK, SPECIES_EMPTY, SPECIES_SAND, TEMP_AMBIENT};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+// ── Life Rules ────────────────────────────────────────────────────────
+
+/// Birth/survival neighbor counts for a Life-like automaton, stored as
+/// bitmasks where bit `n` means "n live neighbors".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LifeRule {
+    birth: u16,
+    survive: u16,
+}
+
+impl LifeRule {
+    /// Conway's Game of Life.
+    pub const CONWAY: LifeRule = LifeRule { birth: 1 << 3, survive: (1 << 2) | (1 << 3) };
+
+    /// Parses rule strings like `B3/S23` (case-insensitive, either order).
+    pub fn parse(rule: &str) -> Option<LifeRule> {
+        let mut birth = None;
+        let mut survive = None;
+        for part in rule.trim().split('/') {
+            let mut chars = part.chars();
+            let slot = match chars.next()?.to_ascii_uppercase() {
+                'B' => &mut birth,
+                'S' => &mut survive,
+                _ => return None,
+            };
+            if slot.is_some() { return None; }
+            let mut mask = 0u16;
+            for c in chars {
+                let n = c.to_digit(10)?;
+                if n > 8 { return None; }
+                mask |= 1 << n;
+            }
+            *slot = Some(mask);
+        }
+        Some(LifeRule { birth: birth?, survive: survive? })
+    }
+
+    #[inline(always)]
+    fn next(&self, alive: bool, neighbors: u32) -> bool {
+        let mask = if alive { self.survive } else { self.birth };
+        mask & (1 << neighbors) != 0
+    }
+}
+
+// ── Layer ─────────────────────────────────────────────────────────────
+
+/// How live cells feed back into the particle simulation.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifeCoupling {
+    /// The layer evolves on its own and never touches the particles.
+    None = 0,
+    /// Each live cell warms the particle beneath it.
+    Heat = 1,
+    /// Live cells over empty space spawn sand.
+    SpawnSand = 2,
+}
+
+/// How the renderer combines the layer with the particle colors.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifeBlend {
+    /// Layer is simulated but not drawn.
+    Hidden = 0,
+    /// Live cells brighten whatever is underneath.
+    Additive = 1,
+    /// Live cells are drawn opaque over the particles.
+    Replace = 2,
+}
+
+/// Heat added per tick to a particle under a live cell in `Heat` mode.
+const LIFE_HEAT_PER_TICK: u8 = 4;
+
+pub(crate) struct LifeLayer {
+    pub(crate) rule: LifeRule,
+    pub(crate) coupling: LifeCoupling,
+    pub(crate) blend: LifeBlend,
+    /// One byte per cell: 1 = alive, 0 = dead.
+    pub(crate) cells: Box<[u8]>,
+    scratch: Box<[u8]>,
+}
+
+impl LifeLayer {
+    pub(crate) fn new(width: usize, height: usize, rule: LifeRule) -> LifeLayer {
+        LifeLayer {
+            rule,
+            coupling: LifeCoupling::None,
+            blend: LifeBlend::Additive,
+            cells: vec![0; width * height].into_boxed_slice(),
+            scratch: vec![0; width * height].into_boxed_slice(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn alive(&self, width: usize, x: usize, y: usize) -> bool {
+        self.cells[y * width + x] != 0
+    }
+
+    pub(crate) fn set(&mut self, width: usize, x: usize, y: usize, alive: bool) {
+        self.cells[y * width + x] = alive as u8;
+    }
+
+    /// Advances the automaton one generation. Cells past the edge count as dead.
+    pub(crate) fn step(&mut self, width: usize, height: usize) {
+        for y in 0..height {
+            for x in 0..width {
+                let mut n = 0;
+                for dy in -1isize..=1 {
+                    for dx in -1isize..=1 {
+                        if dx == 0 && dy == 0 { continue; }
+                        let nx = x as isize + dx;
+                        let ny = y as isize + dy;
+                        if nx < 0 || ny < 0 || nx as usize >= width || ny as usize >= height { continue; }
+                        n += self.cells[ny as usize * width + nx as usize] as u32;
+                    }
+                }
+                let i = y * width + x;
+                self.scratch[i] = self.rule.next(self.cells[i] != 0, n) as u8;
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.scratch);
+    }
+
+    /// Applies the coupling mode to the particle grid.
+    pub(crate) fn couple(&self, cells: &mut [u8], width: usize, height: usize, clock: u8, chunks: &mut ChunkMap) {
+        if self.coupling == LifeCoupling::None { return; }
+        for y in 0..height {
+            for x in 0..width {
+                if !self.alive(width, x, y) { continue; }
+                let i = cell_idx(width, x, y);
+                match self.coupling {
+                    LifeCoupling::Heat => {
+                        cells[i + 2] = cells[i + 2].saturating_add(LIFE_HEAT_PER_TICK);
+                    }
+                    LifeCoupling::SpawnSand if cells[i] == SPECIES_EMPTY => {
+                        cells[i] = SPECIES_SAND;
+                        cells[i + 1] = crate::rand_ra();
+                        cells[i + 2] = TEMP_AMBIENT;
+                        cells[i + CELL_CLOCK] = clock;
+                        chunks.wake_area(x, y, 1);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_standard_rules() {
+        assert_eq!(LifeRule::parse("B3/S23"), Some(LifeRule::CONWAY));
+        assert_eq!(LifeRule::parse("s23/b3"), Some(LifeRule::CONWAY));
+        let highlife = LifeRule::parse("B36/S23").unwrap();
+        assert!(highlife.next(false, 6));
+        let seeds = LifeRule::parse("B2/S").unwrap();
+        assert!(!seeds.next(true, 2));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_rules() {
+        assert_eq!(LifeRule::parse(""), None);
+        assert_eq!(LifeRule::parse("B3"), None);
+        assert_eq!(LifeRule::parse("B9/S23"), None);
+        assert_eq!(LifeRule::parse("B3/B3"), None);
+        assert_eq!(LifeRule::parse("X3/S23"), None);
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let mut layer = LifeLayer::new(5, 5, LifeRule::CONWAY);
+        for x in 1..4 { layer.set(5, x, 2, true); }
+        layer.step(5, 5);
+        for y in 1..4 { assert!(layer.alive(5, 2, y)); }
+        assert!(!layer.alive(5, 1, 2));
+        layer.step(5, 5);
+        for x in 1..4 { assert!(layer.alive(5, x, 2)); }
+        assert!(!layer.alive(5, 2, 1));
+    }
+}
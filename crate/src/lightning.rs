@@ -0,0 +1,138 @@
+use crate::chunks::ChunkMap;
+use crate::events::{Event, EventSink};
+use crate::{
+    cell_idx, in_bounds, is_pinned, rand, rand_range, set_cell_raw, FISH_DEAD, SPECIES_EMBER, SPECIES_EMPTY,
+    SPECIES_FIRE, SPECIES_FISH, SPECIES_GAS, SPECIES_LIGHTNING, SPECIES_RADIATION, SPECIES_SALTWATER, SPECIES_SMOKE,
+    SPECIES_STEAM, SPECIES_HYDROGEN, SPECIES_OXYGEN,
+    SPECIES_TOXIC_GAS, SPECIES_WALL, SPECIES_WATER,
+};
+
+/// Heat of the bolt and of whatever it strikes: enough to fuse sand into
+/// glass and set oil and gas alight.
+pub(crate) const LIGHTNING_HEAT: u8 = 220;
+
+// A bolt's `ra` counts down the ticks it has left to flicker.
+const BOLT_LIFE_MIN: u8 = 3;
+pub(crate) const BOLT_LIFE_MAX: u8 = 6;
+/// Chance per row that the bolt jags a cell to one side.
+const BOLT_JAG_CHANCE: f64 = 0.45;
+/// Most cells of water a single strike runs through.
+const SHOCK_REACH: usize = 1024;
+
+const NEIGHBORS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// What a bolt passes straight through: open air, gases, flames and
+/// radiation. Gas is heated on the way past rather than replaced, so the
+/// pocket goes off.
+#[inline(always)]
+fn is_open(species: u8) -> bool {
+    matches!(
+        species,
+        SPECIES_EMPTY | SPECIES_SMOKE | SPECIES_STEAM | SPECIES_GAS | SPECIES_TOXIC_GAS | SPECIES_FIRE | SPECIES_EMBER
+            | SPECIES_LIGHTNING | SPECIES_RADIATION | SPECIES_HYDROGEN | SPECIES_OXYGEN
+    )
+}
+
+#[inline(always)]
+fn conducts_shock(species: u8) -> bool {
+    matches!(species, SPECIES_WATER | SPECIES_SALTWATER | SPECIES_FISH)
+}
+
+/// Traces a bolt from the top of column `x` down to the first cell it
+/// can't pass through, jagging a cell left or right now and then. Open
+/// cells along the way become short-lived bolt cells at `LIGHTNING_HEAT`,
+/// and the cell struck is superheated; striking water runs the current
+/// through the whole connected body, killing any fish in it. Walls and
+/// pinned cells stop the bolt without being touched. Returns where it
+/// struck, or None if it reached the bottom edge without hitting anything.
+pub(crate) fn strike(
+    cells: &mut [u8], width: usize, height: usize, x: usize, clock: u8, chunks: &mut ChunkMap, events: &mut EventSink,
+) -> Option<(usize, usize)> {
+    let mut x = x;
+    for y in 0..height {
+        if y > 0 && rand() < BOLT_JAG_CHANCE {
+            let nx = if rand() < 0.5 { x.wrapping_sub(1) } else { x + 1 };
+            if nx < width { x = nx; }
+        }
+        let i = cell_idx(width, x, y);
+        let species = cells[i];
+        if is_open(species) && !is_pinned(cells, i) {
+            if matches!(species, SPECIES_GAS | SPECIES_HYDROGEN) {
+                cells[i + 2] = cells[i + 2].max(LIGHTNING_HEAT);
+            } else {
+                let life = rand_range(BOLT_LIFE_MIN, BOLT_LIFE_MAX);
+                set_cell_raw(cells, width, x, y, SPECIES_LIGHTNING, life, LIGHTNING_HEAT, clock);
+            }
+            chunks.wake_area(x, y, 1);
+            continue;
+        }
+
+        events.push(Event::Struck { x, y, species });
+        if species != SPECIES_WALL && !is_pinned(cells, i) {
+            if conducts_shock(species) { shock(cells, width, height, x, y); }
+            cells[i + 2] = cells[i + 2].max(LIGHTNING_HEAT);
+        }
+        chunks.wake_area(x, y, 2);
+        return Some((x, y));
+    }
+    None
+}
+
+/// Runs current through the body of water (and anything swimming in it)
+/// connected to (x, y), up to `SHOCK_REACH` cells.
+fn shock(cells: &mut [u8], width: usize, height: usize, x: usize, y: usize) {
+    let mut seen = vec![false; width * height];
+    let mut frontier = vec![(x, y)];
+    seen[y * width + x] = true;
+    let mut reached = 0;
+    while let Some((x, y)) = frontier.pop() {
+        reached += 1;
+        if reached > SHOCK_REACH { break; }
+        let i = cell_idx(width, x, y);
+        if cells[i] == SPECIES_FISH { cells[i + 1] = FISH_DEAD; }
+        for &(dx, dy) in &NEIGHBORS {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !in_bounds(width, height, nx, ny) { continue; }
+            let (nx, ny) = (nx as usize, ny as usize);
+            let ni = cell_idx(width, nx, ny);
+            if seen[ny * width + nx] || !conducts_shock(cells[ni]) || is_pinned(cells, ni) { continue; }
+            seen[ny * width + nx] = true;
+            frontier.push((nx, ny));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_species, seed_rng, CELL_STRIDE, SPECIES_SAND, TEMP_AMBIENT};
+
+    fn grid(width: usize, height: usize) -> (Vec<u8>, ChunkMap) {
+        (vec![0u8; width * height * CELL_STRIDE], ChunkMap::new(width, height))
+    }
+
+    #[test]
+    fn bolt_stops_at_the_first_solid_and_heats_it() {
+        seed_rng(42);
+        let (mut cells, mut chunks) = grid(9, 12);
+        for x in 0..9 { set_cell_raw(&mut cells, 9, x, 10, SPECIES_SAND, 0, TEMP_AMBIENT, 0); }
+        let mut events = EventSink::new(true);
+        let (hx, hy) = strike(&mut cells, 9, 12, 4, 1, &mut chunks, &mut events).expect("bolt hits the sand");
+        assert_eq!(hy, 10);
+        assert!(cells[cell_idx(9, hx, hy) + 2] >= LIGHTNING_HEAT);
+        assert_eq!(*events, vec![Event::Struck { x: hx, y: hy, species: SPECIES_SAND }]);
+        let bolt = (0..10).filter(|&y| (0..9).any(|x| get_species(&cells, 9, x, y) == SPECIES_LIGHTNING)).count();
+        assert_eq!(bolt, 10, "Every row above the strike carries a bolt cell");
+    }
+
+    #[test]
+    fn walls_stop_the_bolt_untouched() {
+        seed_rng(42);
+        let (mut cells, mut chunks) = grid(1, 6);
+        set_cell_raw(&mut cells, 1, 0, 3, SPECIES_WALL, 0, TEMP_AMBIENT, 0);
+        assert_eq!(strike(&mut cells, 1, 6, 0, 1, &mut chunks, &mut EventSink::default()), Some((0, 3)));
+        assert_eq!(cells[cell_idx(1, 0, 3) + 2], TEMP_AMBIENT);
+        assert_eq!(get_species(&cells, 1, 0, 4), SPECIES_EMPTY);
+    }
+}
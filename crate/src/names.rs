@@ -0,0 +1,55 @@
+use crate::custom::{CUSTOM_SPECIES_FIRST, CUSTOM_SPECIES_LAST};
+use crate::SPECIES_COUNT;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Built-in species names by ID.
+const SPECIES_NAMES: [&str; SPECIES_COUNT] = [
+    "empty", "sand", "water", "oil", "wall", "fire", "plant", "steam", "lava", "stone", "ice", "smoke",
+    "acid", "wood", "ash", "charcoal", "gunpowder", "salt", "saltwater", "seed", "virus", "clone", "void",
+    "metal", "battery", "switch", "gas", "snow", "heater", "cooler", "ember", "toxic_gas",
+    "mercury", "glass", "fuse", "thermite", "wax", "molten_wax", "dirt", "mud", "ant", "fish", "nitro", "lightning",
+    "gravel", "wet_sand", "foam", "soap", "uranium", "lead", "radiation", "hydrogen", "oxygen",
+];
+
+/// Custom species have no names of their own, so they're written as this
+/// prefix and their ID.
+const CUSTOM_PREFIX: &str = "custom_";
+
+/// The name JSON scenes and tools use for `species`: `"sand"`, `"oxygen"`,
+/// or `"custom_64"` for anything past the built-ins.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn species_name(species: u8) -> String {
+    match SPECIES_NAMES.get(species as usize) {
+        Some(name) => name.to_string(),
+        None => format!("{CUSTOM_PREFIX}{species}"),
+    }
+}
+
+/// The species called `name`, as `species_name` writes it. Custom names
+/// must be in the custom ID range, though the species needn't be
+/// registered.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn species_id(name: &str) -> Option<u8> {
+    if let Some(id) = SPECIES_NAMES.iter().position(|&n| n == name) { return Some(id as u8); }
+    let id: u8 = name.strip_prefix(CUSTOM_PREFIX)?.parse().ok()?;
+    (CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).contains(&id).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_OXYGEN, SPECIES_SAND};
+
+    #[test]
+    fn names_round_trip() {
+        for id in (0..SPECIES_COUNT as u8).chain([CUSTOM_SPECIES_FIRST, CUSTOM_SPECIES_LAST]) {
+            assert_eq!(species_id(&species_name(id)), Some(id));
+        }
+        assert_eq!(species_id("sand"), Some(SPECIES_SAND));
+        assert_eq!(species_id("oxygen"), Some(SPECIES_OXYGEN));
+        assert_eq!(species_id("custom_1"), None, "Not a custom ID");
+        assert_eq!(species_id("sandd"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use rayon::prelude::*;
+
+use crate::chunks::{ChunkMap, CHUNK_SIZE};
+use crate::events::EventSink;
+use crate::explosion::MAX_FLING;
+use crate::properties::MAX_SPREAD;
+use crate::{rand_bool, rand_u32, reseed_rng, rng_state, step_cell, xorshift, World, GUNPOWDER_BLAST_RADIUS, SOAP_EXTRA_REACH};
+
+/// Furthest from its own position a single cell update may read or write:
+/// a gunpowder blast scans twice its radius and flings debris further.
+/// Nitro blasts and gas pockets reach further still, so the movement pass
+/// only primes them and `phase_transitions` sets them off.
+const MAX_REACH: usize = 16;
+
+const _: () = assert!(2 * GUNPOWDER_BLAST_RADIUS + MAX_FLING <= MAX_REACH);
+const _: () = assert!(MAX_SPREAD as usize + SOAP_EXTRA_REACH as usize <= MAX_REACH);
+
+// Chunks updated side by side are a whole chunk apart, so their reaches
+// can't meet in the gap.
+const _: () = assert!(2 * MAX_REACH <= CHUNK_SIZE);
+
+/// Raw view of the cell buffer handed to worker threads.
+struct SharedCells(*mut u8, usize);
+
+// SAFETY: see `movement_pass` — concurrent tasks touch disjoint bytes.
+unsafe impl Send for SharedCells {}
+unsafe impl Sync for SharedCells {}
+
+/// Runs the movement pass over active chunks on the rayon pool.
+///
+/// Chunks are split into four checkerboard phases by (column, row) parity;
+/// within a phase every pair of chunks has a full chunk between them, so
+/// with `MAX_REACH` at most half a chunk no two tasks touch the same cell.
+/// Phases run one after another. Each chunk reseeds the thread's RNG from
+/// the tick seed and its own index, so results for a given seed don't
+/// depend on the thread count or scheduling. Events are merged in task
+/// order for the same reason.
+pub(crate) fn movement_pass(world: &mut World) {
+    let w = world.width;
+    let h = world.height;
+    let clk = world.clock;
+    let (cols, rows) = world.chunks.dims();
+    let tick_seed = rand_u32();
+    // The calling thread may run tasks itself; put its stream back after.
+    let caller_rng = rng_state();
+    let shared = SharedCells(world.cells.as_mut_ptr(), world.cells.len());
+
+    for phase in 0..4 {
+        let tasks: Vec<(usize, usize)> = (0..rows)
+            .filter(|cy| cy % 2 == phase / 2)
+            .flat_map(|cy| (0..cols).filter(move |cx| cx % 2 == phase % 2).map(move |cx| (cx, cy)))
+            .filter(|&(cx, cy)| world.chunks.is_chunk_active(cx, cy))
+            .collect();
+
+        let chunks = &world.chunks;
+        let config = &world.config;
+        let forces = world.forces.as_ref();
+        let wind = world.wind.as_ref();
+        let events_on = world.tick_events.is_on();
+        let shared = &shared;
+        let results: Vec<(ChunkMap, u32, EventSink)> = tasks
+            .par_iter()
+            .map(|&(cx, cy)| {
+                // SAFETY: the buffer outlives the pass and tasks in this
+                // phase only reach cells within MAX_REACH of their chunk.
+                let cells = unsafe { std::slice::from_raw_parts_mut(shared.0, shared.1) };
+                let mut local = chunks.fork();
+                let mut moved = 0;
+                let mut events = EventSink::new(events_on);
+                reseed_rng(xorshift(tick_seed ^ ((cy * cols + cx) as u32).wrapping_mul(0x9E37_79B9)));
+
+                let x0 = cx * CHUNK_SIZE;
+                let x1 = (x0 + CHUNK_SIZE).min(w);
+                for y in (cy * CHUNK_SIZE..((cy + 1) * CHUNK_SIZE).min(h)).rev() {
+                    let left_to_right = rand_bool();
+                    for step in 0..x1 - x0 {
+                        let x = if left_to_right { x0 + step } else { x1 - 1 - step };
+                        if !local.is_live(x, y) { continue; }
+                        moved += step_cell(cells, w, h, x, y, clk, config, forces, wind, &mut local, &mut events) as u32;
+                    }
+                }
+                (local, moved, events)
+            })
+            .collect();
+
+        for (local, moved, mut events) in results {
+            world.chunks.merge(&local);
+            world.moved += moved;
+            world.tick_events.append(&mut events);
+        }
+    }
+    world.rows_left = 0;
+    reseed_rng(caller_rng);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{World, SPECIES_SAND, SPECIES_WALL, SPECIES_WATER};
+
+    fn run(threads: usize) -> Vec<u8> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        pool.install(|| {
+            let mut w = World::new(130, 100);
+            for x in 0..130 { w.set_cell(x, 99, SPECIES_WALL); }
+            for y in 0..40 {
+                for x in (y % 3..130).step_by(3) {
+                    w.set_cell(x, y, if x % 2 == 0 { SPECIES_SAND } else { SPECIES_WATER });
+                }
+            }
+            for _ in 0..40 { w.tick(); }
+            w.cells.to_vec()
+        })
+    }
+
+    #[test]
+    fn result_is_independent_of_thread_count() {
+        let single = run(1);
+        assert_eq!(single, run(4));
+        assert_eq!(single, run(4));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::clipboard::Clipboard;
+use crate::rle::{read_varint, write_varint};
+use crate::custom::is_species_id;
+use crate::SPECIES_EMPTY;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Leads every encoded prefab, followed by the format version.
+const MAGIC: &[u8; 4] = b"SPFB";
+const VERSION: u8 = 1;
+
+/// Largest prefab `from_bytes` will allocate. Prefabs are meant to be
+/// structures dropped into a scene, not whole scenes.
+const MAX_CELLS: usize = 1 << 20;
+
+/// A named, ready-made structure (a house, a volcano, an aqueduct) that
+/// can be shipped in a library and dropped into any scene with
+/// `World::place_prefab`.
+///
+/// Only species are stored: placed cells start as if painted by hand.
+/// Empty cells are transparent, so a prefab only ever adds to a scene.
+///
+/// The byte format mirrors the RLE scene format:
+///
+/// ```text
+/// "SPFB" version:u8 name:str author:str width:varint height:varint
+/// { length:varint species:u8 }*
+/// ```
+///
+/// where a `str` is a varint byte length followed by UTF-8.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prefab {
+    name: String,
+    author: String,
+    width: usize,
+    height: usize,
+    /// Species IDs, row by row.
+    species: Vec<u8>,
+}
+
+impl Prefab {
+    /// A prefab lifted from a clipboard, keeping only its species.
+    pub fn from_clipboard(clipboard: &Clipboard, name: &str, author: &str) -> Prefab {
+        let (w, h) = (clipboard.width(), clipboard.height());
+        let mut species = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                species.push(clipboard.cell(x, y)[0]);
+            }
+        }
+        Prefab { name: name.to_string(), author: author.to_string(), width: w, height: h, species }
+    }
+
+    /// The species at (x, y) within the prefab.
+    pub(crate) fn species_at(&self, x: usize, y: usize) -> u8 {
+        self.species[y * self.width + x]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let total = self.width.checked_mul(self.height).filter(|&n| n <= MAX_CELLS).ok_or("prefab too large")?;
+        if self.species.len() != total { return Err("species don't match the prefab size".into()); }
+        if let Some(&s) = self.species.iter().find(|&&s| !is_species_id(s)) {
+            return Err(format!("unknown species {s}"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Prefab {
+    /// A `width` x `height` prefab from species IDs, row by row. Fails if
+    /// the buffer doesn't match the size or holds an unknown species.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new(name: &str, author: &str, width: usize, height: usize, species: Vec<u8>) -> Result<Prefab, String> {
+        let prefab = Prefab { name: name.to_string(), author: author.to_string(), width, height, species };
+        prefab.validate()?;
+        Ok(prefab)
+    }
+
+    pub fn name(&self) -> String { self.name.clone() }
+    pub fn author(&self) -> String { self.author.clone() }
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    /// The prefab in its compact byte format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.name.len() + self.author.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        for s in [&self.name, &self.author] {
+            write_varint(&mut out, s.len());
+            out.extend_from_slice(s.as_bytes());
+        }
+        write_varint(&mut out, self.width);
+        write_varint(&mut out, self.height);
+
+        let mut cells = self.species.iter().copied();
+        let Some(mut run) = cells.next() else { return out; };
+        let mut len = 1;
+        for species in cells {
+            if species == run {
+                len += 1;
+                continue;
+            }
+            write_varint(&mut out, len);
+            out.push(run);
+            run = species;
+            len = 1;
+        }
+        write_varint(&mut out, len);
+        out.push(run);
+        out
+    }
+
+    /// Parses `to_bytes`' output.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Prefab, String> {
+        let rest = bytes.strip_prefix(MAGIC.as_slice()).ok_or("not a prefab")?;
+        let (&version, mut rest) = rest.split_first().ok_or("truncated header")?;
+        if version != VERSION { return Err(format!("unsupported prefab version {version}")); }
+        let name = read_str(&mut rest)?;
+        let author = read_str(&mut rest)?;
+        let width = read_varint(&mut rest)?;
+        let height = read_varint(&mut rest)?;
+        let total = width.checked_mul(height).filter(|&n| n <= MAX_CELLS).ok_or("prefab too large")?;
+
+        let mut species = Vec::with_capacity(total);
+        while !rest.is_empty() {
+            let len = read_varint(&mut rest)?;
+            let (&s, tail) = rest.split_first().ok_or("truncated run")?;
+            rest = tail;
+            if len == 0 || len > total - species.len() { return Err("runs don't match the prefab size".into()); }
+            species.resize(species.len() + len, s);
+        }
+        Prefab::new(&name, &author, width, height, species)
+    }
+
+    /// True if placing the prefab would change nothing.
+    pub fn is_empty(&self) -> bool { self.species.iter().all(|&s| s == SPECIES_EMPTY) }
+}
+
+// JSON library files: needs the `serde` feature.
+#[cfg(feature = "serde")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Prefab {
+    /// The prefab as JSON: name, author, size and a row-major `species`
+    /// array of IDs.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Parses `to_json`' output, or a hand-written file in the same shape.
+    pub fn from_json(json: &str) -> Result<Prefab, String> {
+        let prefab: Prefab = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        prefab.validate()?;
+        Ok(prefab)
+    }
+}
+
+fn read_str(bytes: &mut &[u8]) -> Result<String, String> {
+    let len = read_varint(bytes)?;
+    if len > bytes.len() { return Err("truncated string".into()); }
+    let (s, rest) = bytes.split_at(len);
+    *bytes = rest;
+    String::from_utf8(s.to_vec()).map_err(|_| "string isn't UTF-8".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_COUNT, SPECIES_STONE, SPECIES_WALL, SPECIES_WOOD};
+
+    fn hut() -> Prefab {
+        let species = vec![
+            SPECIES_EMPTY, SPECIES_WOOD, SPECIES_EMPTY,
+            SPECIES_WOOD, SPECIES_EMPTY, SPECIES_WOOD,
+            SPECIES_STONE, SPECIES_STONE, SPECIES_STONE,
+        ];
+        Prefab::new("hut", "tests", 3, 3, species).unwrap()
+    }
+
+    #[test]
+    fn new_rejects_bad_grids() {
+        assert!(Prefab::new("a", "b", 2, 2, vec![SPECIES_WALL; 3]).is_err(), "Wrong size");
+        assert!(Prefab::new("a", "b", 1, 1, vec![SPECIES_COUNT as u8]).is_err(), "Unknown species");
+        assert!(Prefab::new("a", "b", 0, 0, Vec::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let prefab = hut();
+        let bytes = prefab.to_bytes();
+        assert_eq!(Prefab::from_bytes(&bytes), Ok(prefab));
+        assert_eq!(Prefab::from_bytes(&Prefab::new("", "", 0, 0, Vec::new()).unwrap().to_bytes()).map(|p| p.width()), Ok(0));
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_input() {
+        let bytes = hut().to_bytes();
+        assert!(Prefab::from_bytes(b"SRLE\x01").is_err());
+        assert!(Prefab::from_bytes(&bytes[..bytes.len() - 1]).is_err(), "Truncated");
+        assert!(Prefab::from_bytes(&bytes[..bytes.len() - 2]).is_err(), "Missing the last run");
+
+        let mut alien = bytes.clone();
+        let last = alien.len() - 1;
+        alien[last] = SPECIES_COUNT as u8;
+        assert!(Prefab::from_bytes(&alien).is_err(), "Unknown species");
+
+        let mut bad_name = Vec::from(MAGIC.as_slice());
+        bad_name.push(VERSION);
+        write_varint(&mut bad_name, 10);
+        bad_name.extend_from_slice(b"hut");
+        assert!(Prefab::from_bytes(&bad_name).is_err(), "Name runs past the end");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_and_validates() {
+        let prefab = hut();
+        let json = prefab.to_json().unwrap();
+        assert!(json.contains("\"name\":\"hut\""));
+        assert_eq!(Prefab::from_json(&json), Ok(prefab));
+        let wrong = r#"{"name":"x","author":"y","width":2,"height":1,"species":[4]}"#;
+        assert!(Prefab::from_json(wrong).is_err());
+    }
+}
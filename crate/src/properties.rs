@@ -0,0 +1,347 @@
+use crate::custom::CUSTOM_SPECIES_LAST;
+use crate::{
+    FUEL_CHARCOAL_MAX, FUEL_CHARCOAL_MIN, FUEL_CRITTER_MAX, FUEL_CRITTER_MIN, FUEL_OIL_MAX, FUEL_OIL_MIN,
+    FUEL_PLANT_MAX, FUEL_PLANT_MIN, FUEL_WAX_MAX, FUEL_WAX_MIN, FUEL_WOOD_MAX, FUEL_WOOD_MIN, SPECIES_ACID, SPECIES_ANT,
+    SPECIES_ASH, SPECIES_BATTERY, SPECIES_CHARCOAL, SPECIES_CLONE, SPECIES_COOLER, SPECIES_DIRT, SPECIES_EMBER,
+    SPECIES_EMPTY, SPECIES_FIRE, SPECIES_FISH, SPECIES_FOAM, SPECIES_FUSE, SPECIES_GAS, SPECIES_GLASS, SPECIES_GRAVEL,
+    SPECIES_GUNPOWDER, SPECIES_HEATER, SPECIES_HYDROGEN, SPECIES_ICE, SPECIES_LAVA, SPECIES_LEAD, SPECIES_LIGHTNING,
+    SPECIES_MERCURY, SPECIES_METAL, SPECIES_MOLTEN_WAX, SPECIES_MUD, SPECIES_NITRO, SPECIES_OIL, SPECIES_OXYGEN,
+    SPECIES_PLANT, SPECIES_RADIATION, SPECIES_SALT, SPECIES_SALTWATER, SPECIES_SAND, SPECIES_SEED, SPECIES_SMOKE,
+    SPECIES_SNOW, SPECIES_SOAP, SPECIES_STEAM, SPECIES_STONE, SPECIES_SWITCH, SPECIES_THERMITE, SPECIES_TOXIC_GAS,
+    SPECIES_URANIUM, SPECIES_VIRUS, SPECIES_VOID, SPECIES_WALL, SPECIES_WATER, SPECIES_WAX, SPECIES_WET_SAND,
+    SPECIES_WOOD, TEMP_CHARCOAL_IGNITE, TEMP_FUSE_IGNITE, TEMP_GAS_IGNITE, TEMP_GUNPOWDER_IGNITE, TEMP_HYDROGEN_IGNITE,
+    TEMP_NITRO_IGNITE, TEMP_OIL_IGNITE, TEMP_PLANT_IGNITE, TEMP_THERMITE_IGNITE, TEMP_WAX_IGNITE, TEMP_WOOD_IGNITE,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// `ignition_temp` of a species that never catches fire.
+pub const IGNITION_NEVER: u8 = u8::MAX;
+
+/// Largest `spread` the table takes. Soapy water runs a little further
+/// still, and the whole run has to stay within the reach the parallel
+/// movement pass allows one update.
+pub(crate) const MAX_SPREAD: u8 = 14;
+
+/// One column of the species property table, for
+/// `World::set_species_property`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpeciesProperty {
+    /// How readily heat passes to and from neighbors.
+    Conductivity = 0,
+    /// Temperature at which the species catches fire or goes off.
+    /// `IGNITION_NEVER` for anything that doesn't burn.
+    IgnitionTemp = 1,
+    /// Fuel range a cell gets when it catches fire, in ticks of burning.
+    FuelMin = 2,
+    FuelMax = 3,
+    /// Relative heaviness: higher sinks below lower.
+    Density = 4,
+    /// Cells a liquid can flow sideways in one tick, before viscosity.
+    /// Values above 14 are taken as 14.
+    Spread = 5,
+    /// Chance per tick, out of 255, that fire lights an adjacent cell of
+    /// the species directly instead of waiting for it to heat up.
+    FireSpread = 6,
+    /// How thick a liquid is, out of 255: the chance it sits out a tick
+    /// instead of flowing, and how much of its `Spread` it reaches when it
+    /// does. 0 runs like water.
+    Viscosity = 7,
+    /// How readily acid eats the species, out of 255: the chance per tick
+    /// that a touching acid cell dissolves it. 0 is immune, 255 dissolves
+    /// on contact.
+    Corrosion = 8,
+    /// How readily a powder slides off a pile, out of 255: the share of
+    /// grains that roll off any slope. The rest only roll where the pile
+    /// drops away by two cells, so lower values pile up steeper. 255 rolls
+    /// like sand.
+    Slip = 9,
+}
+
+/// Tunable physical constants of one species.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Properties {
+    pub conductivity: u8,
+    pub ignition_temp: u8,
+    pub fuel_min: u8,
+    pub fuel_max: u8,
+    pub density: u8,
+    pub spread: u8,
+    pub fire_spread: u8,
+    pub viscosity: u8,
+    pub corrosion: u8,
+    pub slip: u8,
+}
+
+impl Properties {
+    /// What a species without an entry of its own gets: conducts a little
+    /// heat, never burns, and sits at the bottom of the density order.
+    pub const INERT: Properties =
+        Properties { conductivity: 5, ignition_temp: IGNITION_NEVER, fuel_min: 0, fuel_max: 0, density: 0, spread: 0, fire_spread: 0, viscosity: 0, corrosion: 0, slip: 255 };
+
+    /// Whether the species burns into fire once hot enough.
+    #[inline]
+    pub fn is_fuel(&self) -> bool {
+        self.ignition_temp != IGNITION_NEVER && self.fuel_max > 0
+    }
+
+    /// Cells a liquid reaches sideways once viscosity has had its say: all
+    /// of `spread` at 0, thinning to 1 as it thickens.
+    #[inline]
+    pub fn reach(&self) -> u8 {
+        ((self.spread as u32 * (255 - self.viscosity as u32)).div_ceil(255)) as u8
+    }
+}
+
+/// Rows in the table: every built-in species and the custom range.
+const TABLE_LEN: usize = CUSTOM_SPECIES_LAST as usize + 1;
+
+/// Per-species property table, indexed by species ID. Starts out with the
+/// simulation's built-in values; custom and unknown species read as inert.
+#[derive(Clone, Debug)]
+pub struct SpeciesProperties {
+    table: [Properties; TABLE_LEN],
+    /// `density` of every ID, apart from the table so the movement pass's
+    /// displacement checks stay a single byte load.
+    densities: [u8; 256],
+}
+
+impl SpeciesProperties {
+    #[inline(always)]
+    pub fn get(&self, species: u8) -> &Properties {
+        self.table.get(species as usize).unwrap_or(&Properties::INERT)
+    }
+
+    #[inline(always)]
+    pub(crate) fn density(&self, species: u8) -> u8 {
+        self.densities[species as usize]
+    }
+
+    #[inline(always)]
+    pub fn conductivity(&self, species: u8) -> u8 {
+        self.get(species).conductivity
+    }
+
+    pub fn value(&self, species: u8, property: SpeciesProperty) -> u8 {
+        let p = self.get(species);
+        match property {
+            SpeciesProperty::Conductivity => p.conductivity,
+            SpeciesProperty::IgnitionTemp => p.ignition_temp,
+            SpeciesProperty::FuelMin => p.fuel_min,
+            SpeciesProperty::FuelMax => p.fuel_max,
+            SpeciesProperty::Density => p.density,
+            SpeciesProperty::Spread => p.spread,
+            SpeciesProperty::FireSpread => p.fire_spread,
+            SpeciesProperty::Viscosity => p.viscosity,
+            SpeciesProperty::Corrosion => p.corrosion,
+            SpeciesProperty::Slip => p.slip,
+        }
+    }
+
+    /// Changes one property of `species`. Ignores unknown species, and
+    /// caps `Spread` at `MAX_SPREAD`.
+    pub fn set(&mut self, species: u8, property: SpeciesProperty, value: u8) {
+        let Some(p) = self.table.get_mut(species as usize) else { return; };
+        match property {
+            SpeciesProperty::Conductivity => p.conductivity = value,
+            SpeciesProperty::IgnitionTemp => p.ignition_temp = value,
+            SpeciesProperty::FuelMin => p.fuel_min = value,
+            SpeciesProperty::FuelMax => p.fuel_max = value,
+            SpeciesProperty::Density => {
+                p.density = value;
+                self.densities[species as usize] = value;
+            }
+            SpeciesProperty::Spread => p.spread = value.min(MAX_SPREAD),
+            SpeciesProperty::FireSpread => p.fire_spread = value,
+            SpeciesProperty::Viscosity => p.viscosity = value,
+            SpeciesProperty::Corrosion => p.corrosion = value,
+            SpeciesProperty::Slip => p.slip = value,
+        }
+    }
+
+    /// Replaces every property of `species` at once. Ignores unknown
+    /// species, and caps `spread` as `set` does.
+    pub fn replace(&mut self, species: u8, properties: Properties) {
+        if let Some(p) = self.table.get_mut(species as usize) {
+            *p = Properties { spread: properties.spread.min(MAX_SPREAD), ..properties };
+            self.densities[species as usize] = properties.density;
+        }
+    }
+}
+
+impl Default for SpeciesProperties {
+    fn default() -> Self {
+        let mut table = [Properties::INERT; TABLE_LEN];
+        let mut set = |species: u8, conductivity: u8, density: u8| {
+            table[species as usize] = Properties { conductivity, density, ..Properties::INERT };
+        };
+        // Solids that never move are as dense as it gets.
+        set(SPECIES_EMPTY, 5, 0);
+        set(SPECIES_SAND, 38, 150);
+        set(SPECIES_WATER, 64, 100);
+        set(SPECIES_OIL, 26, 80);
+        set(SPECIES_WALL, 13, 255);
+        set(SPECIES_FIRE, 102, 3);
+        set(SPECIES_PLANT, 20, 255);
+        set(SPECIES_STEAM, 8, 4);
+        set(SPECIES_LAVA, 90, 180);
+        set(SPECIES_STONE, 51, 200);
+        set(SPECIES_ICE, 77, 92);
+        set(SPECIES_SMOKE, 5, 5);
+        set(SPECIES_ACID, 51, 105);
+        set(SPECIES_WOOD, 20, 70);
+        set(SPECIES_ASH, 10, 60);
+        set(SPECIES_CHARCOAL, 30, 255);
+        set(SPECIES_GUNPOWDER, 25, 140);
+        set(SPECIES_SALT, 30, 145);
+        set(SPECIES_SALTWATER, 70, 110);
+        set(SPECIES_SEED, 20, 120);
+        set(SPECIES_VIRUS, 20, 255);
+        set(SPECIES_CLONE, 5, 255);
+        set(SPECIES_VOID, 5, 255);
+        set(SPECIES_METAL, 110, 255);
+        set(SPECIES_BATTERY, 40, 255);
+        set(SPECIES_SWITCH, 60, 255);
+        set(SPECIES_GAS, 5, 2);
+        set(SPECIES_SNOW, 15, 40);
+        set(SPECIES_HEATER, 100, 255);
+        set(SPECIES_COOLER, 100, 255);
+        set(SPECIES_EMBER, 60, 3);
+        set(SPECIES_TOXIC_GAS, 5, 3);
+        // Heavier than stone: sinks through everything that moves.
+        set(SPECIES_MERCURY, 100, 230);
+        set(SPECIES_GLASS, 12, 255);
+        set(SPECIES_FUSE, 15, 255);
+        // Sinks through the lava it makes, but not through stone.
+        set(SPECIES_THERMITE, 40, 190);
+        set(SPECIES_WAX, 15, 255);
+        set(SPECIES_MOLTEN_WAX, 20, 90);
+        set(SPECIES_DIRT, 30, 140);
+        set(SPECIES_MUD, 45, 170);
+        // Heavy enough that water can't wash an ant off its feet.
+        set(SPECIES_ANT, 10, 120);
+        // Live fish swim on their own; a dead one just sinks.
+        set(SPECIES_FISH, 30, 105);
+        set(SPECIES_NITRO, 30, 110);
+        set(SPECIES_LIGHTNING, 102, 1);
+        // Coarser and heavier than sand.
+        set(SPECIES_GRAVEL, 45, 170);
+        // The water in it carries heat better, but it's no heavier for it.
+        set(SPECIES_WET_SAND, 50, 150);
+        // Mostly air: floats on every liquid and barely passes on heat.
+        set(SPECIES_FOAM, 8, 10);
+        set(SPECIES_SOAP, 15, 255);
+        // Uranium sinks through mercury; the lead it leaves rests on it.
+        set(SPECIES_URANIUM, 30, 235);
+        set(SPECIES_LEAD, 35, 225);
+        set(SPECIES_RADIATION, 5, 1);
+        set(SPECIES_HYDROGEN, 8, 1);
+        set(SPECIES_OXYGEN, 5, 4);
+
+        let mut burn = |species: u8, ignition_temp: u8, fuel_min: u8, fuel_max: u8| {
+            let p = &mut table[species as usize];
+            (p.ignition_temp, p.fuel_min, p.fuel_max) = (ignition_temp, fuel_min, fuel_max);
+        };
+        burn(SPECIES_OIL, TEMP_OIL_IGNITE, FUEL_OIL_MIN, FUEL_OIL_MAX);
+        burn(SPECIES_PLANT, TEMP_PLANT_IGNITE, FUEL_PLANT_MIN, FUEL_PLANT_MAX);
+        burn(SPECIES_SEED, TEMP_PLANT_IGNITE, FUEL_PLANT_MIN, FUEL_PLANT_MAX);
+        burn(SPECIES_WOOD, TEMP_WOOD_IGNITE, FUEL_WOOD_MIN, FUEL_WOOD_MAX);
+        burn(SPECIES_CHARCOAL, TEMP_CHARCOAL_IGNITE, FUEL_CHARCOAL_MIN, FUEL_CHARCOAL_MAX);
+        burn(SPECIES_WAX, TEMP_WAX_IGNITE, FUEL_WAX_MIN, FUEL_WAX_MAX);
+        burn(SPECIES_MOLTEN_WAX, TEMP_WAX_IGNITE, FUEL_WAX_MIN, FUEL_WAX_MAX);
+        burn(SPECIES_ANT, TEMP_PLANT_IGNITE, FUEL_CRITTER_MIN, FUEL_CRITTER_MAX);
+        burn(SPECIES_FISH, TEMP_PLANT_IGNITE, FUEL_CRITTER_MIN, FUEL_CRITTER_MAX);
+        // Explosives go off instead of burning, so they carry no fuel.
+        burn(SPECIES_GUNPOWDER, TEMP_GUNPOWDER_IGNITE, 0, 0);
+        burn(SPECIES_NITRO, TEMP_NITRO_IGNITE, 0, 0);
+        burn(SPECIES_GAS, TEMP_GAS_IGNITE, 0, 0);
+        burn(SPECIES_HYDROGEN, TEMP_HYDROGEN_IGNITE, 0, 0);
+        // Fuse and thermite burn out on their own clock rather than as fire.
+        burn(SPECIES_FUSE, TEMP_FUSE_IGNITE, 0, 0);
+        burn(SPECIES_THERMITE, TEMP_THERMITE_IGNITE, 0, 0);
+
+        for (species, spread) in [(SPECIES_WATER, 2), (SPECIES_SALTWATER, 2), (SPECIES_ACID, 2), (SPECIES_OIL, 1), (SPECIES_LAVA, 1), (SPECIES_MERCURY, 2), (SPECIES_MOLTEN_WAX, 1), (SPECIES_NITRO, 2)] {
+            table[species as usize].spread = spread;
+        }
+        // Oil and wax ooze and lava crawls where water runs.
+        for (species, viscosity) in
+            [(SPECIES_SALTWATER, 10), (SPECIES_ACID, 20), (SPECIES_OIL, 70), (SPECIES_MOLTEN_WAX, 120), (SPECIES_LAVA, 180)]
+        {
+            table[species as usize].viscosity = viscosity;
+        }
+        // Acid eats the soft and organic; metal, glass and the liquids shrug it
+        // off. Walls only go at all when `acid_affects_walls` is set.
+        for species in [
+            SPECIES_SAND, SPECIES_STONE, SPECIES_PLANT, SPECIES_WOOD, SPECIES_ICE, SPECIES_ASH, SPECIES_CHARCOAL, SPECIES_SALT,
+            SPECIES_SEED, SPECIES_VIRUS, SPECIES_SNOW, SPECIES_FUSE, SPECIES_THERMITE, SPECIES_DIRT, SPECIES_MUD,
+            SPECIES_GRAVEL, SPECIES_WET_SAND, SPECIES_SOAP,
+        ] {
+            table[species as usize].corrosion = 51;
+        }
+        table[SPECIES_WALL as usize].corrosion = 5;
+        // Critters don't last a moment in it.
+        table[SPECIES_ANT as usize].corrosion = 255;
+        table[SPECIES_FISH as usize].corrosion = 255;
+        // Sand and the fine powders roll off anything; coarser or clumpier
+        // grains hold a steeper pile, and wet soil steepest of all. Wet sand
+        // doesn't roll at all, so it can be built into walls.
+        for (species, slip) in
+            [(SPECIES_SNOW, 128), (SPECIES_DIRT, 160), (SPECIES_GRAVEL, 64), (SPECIES_MUD, 24), (SPECIES_WET_SAND, 0)]
+        {
+            table[species as usize].slip = slip;
+        }
+        // Flames skate across an oil slick but take a while to catch on wood.
+        for (species, chance) in [(SPECIES_OIL, 255), (SPECIES_PLANT, 32), (SPECIES_WOOD, 8), (SPECIES_GUNPOWDER, 255), (SPECIES_NITRO, 255), (SPECIES_FUSE, 255)] {
+            table[species as usize].fire_spread = chance;
+        }
+        let mut densities = [Properties::INERT.density; 256];
+        for (d, p) in densities.iter_mut().zip(&table) { *d = p.density; }
+        SpeciesProperties { table, densities }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties_round_trip_and_ignore_unknown_species() {
+        let mut props = SpeciesProperties::default();
+        assert_eq!(props.value(SPECIES_WOOD, SpeciesProperty::IgnitionTemp), TEMP_WOOD_IGNITE);
+        assert!(props.get(SPECIES_WOOD).is_fuel());
+        assert!(!props.get(SPECIES_GUNPOWDER).is_fuel(), "Gunpowder explodes rather than burning");
+        props.set(SPECIES_SAND, SpeciesProperty::Spread, 3);
+        assert_eq!(props.get(SPECIES_SAND).spread, 3);
+        props.set(SPECIES_WATER, SpeciesProperty::Spread, 4);
+        assert_eq!(props.get(SPECIES_WATER).reach(), 4);
+        props.set(SPECIES_WATER, SpeciesProperty::Viscosity, 128);
+        assert_eq!(props.get(SPECIES_WATER).reach(), 2);
+        props.set(SPECIES_WATER, SpeciesProperty::Viscosity, 255);
+        assert_eq!(props.get(SPECIES_WATER).reach(), 0);
+        props.set(200, SpeciesProperty::Density, 9);
+        assert_eq!(props.value(200, SpeciesProperty::Density), 0);
+    }
+
+    #[test]
+    fn spread_is_capped() {
+        let mut props = SpeciesProperties::default();
+        props.set(SPECIES_OIL, SpeciesProperty::Spread, 255);
+        assert_eq!(props.get(SPECIES_OIL).spread, MAX_SPREAD);
+        props.replace(CUSTOM_SPECIES_LAST, Properties { spread: 40, ..Properties::INERT });
+        assert_eq!(props.get(CUSTOM_SPECIES_LAST).reach(), MAX_SPREAD);
+    }
+
+    #[test]
+    fn density_lookup_follows_edits() {
+        let mut props = SpeciesProperties::default();
+        assert_eq!(props.density(SPECIES_SAND), props.get(SPECIES_SAND).density);
+        props.set(SPECIES_SAND, SpeciesProperty::Density, 90);
+        assert_eq!(props.density(SPECIES_SAND), 90);
+        props.replace(CUSTOM_SPECIES_LAST, Properties { density: 120, ..Properties::INERT });
+        assert_eq!(props.density(CUSTOM_SPECIES_LAST), 120);
+        props.set(200, SpeciesProperty::Density, 9);
+        assert_eq!(props.density(200), 0);
+    }
+}
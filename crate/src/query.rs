@@ -0,0 +1,130 @@
+use crate::{CELL_AUX1, CELL_AUX2, CELL_CLOCK, CELL_STRIDE};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Decoded copy of one cell, so callers don't need to know the byte
+/// layout of the cell buffer.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellInfo {
+    pub species: u8,
+    /// Per-species register: color noise, fuel, growth stage, ...
+    pub ra: u8,
+    pub temperature: u8,
+    /// Extra per-species state: a falling body's speed.
+    pub aux1: u8,
+    /// Extra per-species state: the fuel a fire cell is burning.
+    pub aux2: u8,
+    /// Update parity in bit 0.
+    pub clock: u8,
+}
+
+impl CellInfo {
+    pub(crate) fn from_bytes(c: &[u8]) -> CellInfo {
+        CellInfo {
+            species: c[0],
+            ra: c[1],
+            temperature: c[2],
+            aux1: c[CELL_AUX1],
+            aux2: c[CELL_AUX2],
+            clock: c[CELL_CLOCK],
+        }
+    }
+
+    /// Species, `ra`, temperature and clock as one little-endian number:
+    /// species in the low byte, clock in the high byte.
+    pub fn packed(&self) -> u32 {
+        u32::from_le_bytes([self.species, self.ra, self.temperature, self.clock])
+    }
+}
+
+/// Inclusive cell rectangle.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_x: usize,
+    pub min_y: usize,
+    pub max_x: usize,
+    pub max_y: usize,
+}
+
+pub(crate) fn count_species(cells: &[u8], species: u8) -> usize {
+    cells.chunks_exact(CELL_STRIDE).filter(|c| c[0] == species).count()
+}
+
+/// Smallest rectangle holding every `species` cell, if there are any.
+pub(crate) fn bounding_box_of(cells: &[u8], width: usize, species: u8) -> Option<BoundingBox> {
+    let mut found: Option<BoundingBox> = None;
+    for (i, c) in cells.chunks_exact(CELL_STRIDE).enumerate() {
+        if c[0] != species { continue; }
+        let (x, y) = (i % width, i / width);
+        found = Some(match found {
+            None => BoundingBox { min_x: x, min_y: y, max_x: x, max_y: y },
+            Some(b) => BoundingBox {
+                min_x: b.min_x.min(x),
+                min_y: b.min_y,
+                max_x: b.max_x.max(x),
+                max_y: y,
+            },
+        });
+    }
+    found
+}
+
+/// FxHash multiplier: cheap to mix, good enough to tell states apart.
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+#[inline(always)]
+fn fx_mix(hash: u64, word: u64) -> u64 {
+    (hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED)
+}
+
+/// FxHash of `header` followed by every cell, one little-endian word per
+/// cell. Not cryptographic; only meant to catch simulations drifting apart.
+pub(crate) fn state_hash(cells: &[u8], header: &[u64]) -> u64 {
+    let hash = header.iter().fold(0, |h, &word| fx_mix(h, word));
+    cells.chunks_exact(CELL_STRIDE).fold(hash, |h, c| {
+        fx_mix(h, u64::from_le_bytes(c.try_into().expect("cells are 8 bytes")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, SPECIES_SAND, SPECIES_WATER};
+
+    #[test]
+    fn packed_matches_memory_order() {
+        let info = CellInfo::from_bytes(&[1, 2, 3, 8, 9, 4, 0, 0]);
+        assert_eq!((info.aux1, info.aux2), (8, 9));
+        assert_eq!(info.packed(), 0x0403_0201, "Aux bytes are left out");
+    }
+
+    #[test]
+    fn bounding_box_spans_all_cells() {
+        let mut cells = vec![0u8; 8 * 6 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 8, 5, 1, SPECIES_SAND, 0, 0, 0);
+        set_cell_raw(&mut cells, 8, 2, 4, SPECIES_SAND, 0, 0, 0);
+        set_cell_raw(&mut cells, 8, 6, 3, SPECIES_SAND, 0, 0, 0);
+        assert_eq!(
+            bounding_box_of(&cells, 8, SPECIES_SAND),
+            Some(BoundingBox { min_x: 2, min_y: 1, max_x: 6, max_y: 4 })
+        );
+        assert_eq!(bounding_box_of(&cells, 8, SPECIES_WATER), None);
+        assert_eq!(count_species(&cells, SPECIES_SAND), 3);
+    }
+
+    #[test]
+    fn state_hash_sees_every_byte() {
+        let mut cells = vec![0u8; 4 * 4 * CELL_STRIDE];
+        let base = state_hash(&cells, &[4, 4]);
+        assert_eq!(state_hash(&cells, &[4, 4]), base);
+        assert_ne!(state_hash(&cells, &[2, 8]), base, "Same cells, different shape");
+        for i in [0, 1, CELL_CLOCK, cells.len() - 1] {
+            cells[i] ^= 1;
+            assert_ne!(state_hash(&cells, &[4, 4]), base, "Byte {i} should count");
+            cells[i] ^= 1;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    cell_idx, in_bounds, SPECIES_ACID, SPECIES_ASH, SPECIES_CHARCOAL, SPECIES_DIRT, SPECIES_EMPTY, SPECIES_GLASS,
+    SPECIES_GRAVEL, SPECIES_ICE, SPECIES_LAVA, SPECIES_METAL, SPECIES_MOLTEN_WAX, SPECIES_MUD, SPECIES_PLANT,
+    SPECIES_SALT, SPECIES_SALTWATER, SPECIES_SAND, SPECIES_SEED, SPECIES_SOAP, SPECIES_STEAM, SPECIES_STONE,
+    SPECIES_VIRUS, SPECIES_WALL, SPECIES_WATER, SPECIES_WAX, SPECIES_WET_SAND, SPECIES_WOOD,
+};
+
+/// One species turning into another, optionally driven by a neighboring
+/// `agent`, and the heat that change gives off (positive, exothermic) or
+/// soaks up (negative, endothermic).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Reaction {
+    pub(crate) reactant: u8,
+    pub(crate) agent: Option<u8>,
+    pub(crate) product: u8,
+    pub(crate) heat: i8,
+}
+
+const fn phase(reactant: u8, product: u8, heat: i8) -> Reaction {
+    Reaction { reactant, agent: None, product, heat }
+}
+
+const fn with(agent: u8, reactant: u8, product: u8, heat: i8) -> Reaction {
+    Reaction { reactant, agent: Some(agent), product, heat }
+}
+
+// Latent heats stay smaller than the hysteresis gap of each transition, or a
+// cell sitting at the threshold would flip back and forth forever.
+pub(crate) const REACTIONS: &[Reaction] = &[
+    phase(SPECIES_ICE, SPECIES_WATER, -2),
+    phase(SPECIES_WATER, SPECIES_ICE, 2),
+    phase(SPECIES_SALTWATER, SPECIES_ICE, 2),
+    phase(SPECIES_WATER, SPECIES_STEAM, -3),
+    phase(SPECIES_STEAM, SPECIES_WATER, 3),
+    phase(SPECIES_SALTWATER, SPECIES_SALT, -3),
+    phase(SPECIES_STONE, SPECIES_LAVA, -3),
+    phase(SPECIES_LAVA, SPECIES_STONE, 3),
+    phase(SPECIES_GRAVEL, SPECIES_LAVA, -3),
+    phase(SPECIES_SAND, SPECIES_GLASS, -3),
+    phase(SPECIES_METAL, SPECIES_LAVA, -3),
+    phase(SPECIES_WAX, SPECIES_MOLTEN_WAX, -1),
+    phase(SPECIES_MOLTEN_WAX, SPECIES_WAX, 1),
+    phase(SPECIES_MUD, SPECIES_DIRT, -3),
+    phase(SPECIES_WET_SAND, SPECIES_SAND, -3),
+    with(SPECIES_ACID, SPECIES_STONE, SPECIES_EMPTY, 12),
+    with(SPECIES_ACID, SPECIES_SAND, SPECIES_EMPTY, 8),
+    with(SPECIES_ACID, SPECIES_WET_SAND, SPECIES_EMPTY, 8),
+    with(SPECIES_ACID, SPECIES_ICE, SPECIES_EMPTY, 4),
+    with(SPECIES_ACID, SPECIES_WOOD, SPECIES_EMPTY, 6),
+    with(SPECIES_ACID, SPECIES_PLANT, SPECIES_EMPTY, 6),
+    with(SPECIES_ACID, SPECIES_SEED, SPECIES_EMPTY, 6),
+    with(SPECIES_ACID, SPECIES_ASH, SPECIES_EMPTY, 4),
+    with(SPECIES_ACID, SPECIES_CHARCOAL, SPECIES_EMPTY, 6),
+    with(SPECIES_ACID, SPECIES_SALT, SPECIES_EMPTY, 4),
+    with(SPECIES_ACID, SPECIES_SOAP, SPECIES_EMPTY, 4),
+    with(SPECIES_ACID, SPECIES_VIRUS, SPECIES_EMPTY, 4),
+    with(SPECIES_ACID, SPECIES_WALL, SPECIES_EMPTY, 10),
+    with(SPECIES_SALT, SPECIES_WATER, SPECIES_SALTWATER, -1),
+];
+
+/// Heat released by `reactant` becoming `product` in the presence of
+/// `agent` (None for plain phase changes). Zero if nothing matches.
+pub(crate) fn heat_of(reactant: u8, agent: Option<u8>, product: u8) -> i8 {
+    REACTIONS
+        .iter()
+        .find(|r| r.reactant == reactant && r.agent == agent && r.product == product)
+        .map_or(0, |r| r.heat)
+}
+
+/// Applies a reaction's heat at (x, y): the full amount to the cell itself
+/// and half to each orthogonal neighbor.
+pub(crate) fn release_heat(cells: &mut [u8], width: usize, height: usize, x: usize, y: usize, heat: i8) {
+    if heat == 0 { return; }
+    let i = cell_idx(width, x, y);
+    cells[i + 2] = (cells[i + 2] as i32 + heat as i32).clamp(0, 255) as u8;
+    let spill = heat as i32 / 2;
+    if spill == 0 { return; }
+    for &(dx, dy) in &[(0isize, -1isize), (-1, 0), (1, 0), (0, 1)] {
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        if !in_bounds(width, height, nx, ny) { continue; }
+        let ni = cell_idx(width, nx as usize, ny as usize);
+        cells[ni + 2] = (cells[ni + 2] as i32 + spill).clamp(0, 255) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_distinguishes_agents() {
+        assert_eq!(heat_of(SPECIES_STONE, Some(SPECIES_ACID), SPECIES_EMPTY), 12);
+        assert_eq!(heat_of(SPECIES_STONE, None, SPECIES_EMPTY), 0);
+        assert!(heat_of(SPECIES_ICE, None, SPECIES_WATER) < 0);
+    }
+
+    #[test]
+    fn release_spills_half_to_neighbors() {
+        let mut cells = vec![0u8; 9 * crate::CELL_STRIDE];
+        for c in cells.chunks_exact_mut(crate::CELL_STRIDE) { c[2] = 20; }
+        release_heat(&mut cells, 3, 3, 1, 1, 8);
+        assert_eq!(cells[cell_idx(3, 1, 1) + 2], 28);
+        assert_eq!(cells[cell_idx(3, 1, 0) + 2], 24);
+        assert_eq!(cells[cell_idx(3, 0, 0) + 2], 20, "Diagonals are untouched");
+        release_heat(&mut cells, 3, 3, 0, 0, -30);
+        assert_eq!(cells[cell_idx(3, 0, 0) + 2], 0, "Temperature clamps at zero");
+    }
+}
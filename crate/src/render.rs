@@ -0,0 +1,782 @@
+use crate::background::{Background, BG_STRIDE};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+use crate::custom::{CUSTOM_SPECIES_FIRST, CUSTOM_SPECIES_LAST};
+use crate::life::{LifeBlend, LifeLayer};
+use crate::{decor, electricity};
+use crate::{
+    surfaces, CELL_STRIDE, SPECIES_ACID, SPECIES_ANT, SPECIES_CHARCOAL, SPECIES_CLONE, SPECIES_COOLER, SPECIES_COUNT, SPECIES_DIRT, SPECIES_EMBER, SPECIES_EMPTY, SPECIES_FIRE, SPECIES_FISH, SPECIES_FOAM, SPECIES_GAS, SPECIES_HEATER, SPECIES_HYDROGEN, SPECIES_ICE, SPECIES_LAVA, SPECIES_LEAD,
+    SPECIES_FUSE, SPECIES_GRAVEL, SPECIES_LIGHTNING, SPECIES_GLASS, SPECIES_MERCURY, SPECIES_METAL, SPECIES_MOLTEN_WAX, SPECIES_MUD, SPECIES_NITRO, SPECIES_OIL, SPECIES_OXYGEN, SPECIES_PLANT, SPECIES_SALTWATER, SPECIES_SAND, SPECIES_SMOKE, SPECIES_SNOW, SPECIES_SOAP, SPECIES_STEAM, SPECIES_STONE,
+    SPECIES_SWITCH, SPECIES_THERMITE, SPECIES_TOXIC_GAS, SPECIES_URANIUM, SPECIES_RADIATION, SPECIES_VIRUS, SPECIES_VOID, SPECIES_WALL, SPECIES_WATER, SPECIES_WAX, SPECIES_WET_SAND, SPECIES_WOOD,
+};
+
+/// Bytes per output pixel.
+pub(crate) const RGBA_STRIDE: usize = 4;
+
+/// Base color per species ID, matching the web frontend's palette.
+pub(crate) const PALETTE: [[u8; 3]; SPECIES_COUNT] = [
+    [26, 26, 46],    // empty
+    [230, 197, 136], // sand
+    [74, 144, 217],  // water
+    [75, 50, 20],    // oil
+    [128, 128, 128], // wall
+    [255, 100, 20],  // fire
+    [34, 139, 34],   // plant
+    [200, 210, 230], // steam
+    [207, 16, 32],   // lava
+    [100, 100, 110], // stone
+    [170, 220, 240], // ice
+    [80, 80, 90],    // smoke
+    [100, 255, 50],  // acid
+    [139, 90, 43],   // wood
+    [150, 148, 140], // ash
+    [45, 38, 34],    // charcoal
+    [62, 60, 58],    // gunpowder
+    [238, 238, 232], // salt
+    [96, 156, 200],  // saltwater
+    [168, 128, 64],  // seed
+    [150, 60, 170],  // virus
+    [205, 180, 70],  // clone
+    [8, 6, 14],      // void
+    [165, 170, 180], // metal
+    [70, 120, 75],   // battery
+    [140, 70, 55],   // switch
+    [170, 190, 120], // gas
+    [240, 244, 252], // snow
+    [150, 62, 40],   // heater
+    [62, 110, 160],  // cooler
+    [255, 140, 40],  // ember
+    [160, 200, 50],  // toxic gas
+    [190, 195, 205], // mercury
+    [200, 228, 236], // glass
+    [96, 78, 58],    // fuse
+    [128, 84, 72],   // thermite
+    [236, 226, 196], // wax
+    [242, 212, 140], // molten wax
+    [118, 84, 54],   // dirt
+    [78, 56, 38],    // mud
+    [44, 26, 22],    // ant
+    [236, 128, 44],  // fish
+    [206, 214, 120], // nitro
+    [236, 240, 255], // lightning
+    [122, 118, 112], // gravel
+    [150, 128, 84],  // wet sand
+    [228, 236, 244], // foam
+    [236, 176, 200], // soap
+    [96, 170, 64],   // uranium
+    [86, 90, 104],   // lead
+    [150, 255, 120], // radiation
+    [214, 226, 250], // hydrogen
+    [150, 200, 240], // oxygen
+];
+
+/// How much of a background cell's color shows through empty space.
+const BG_SHADE: f32 = 0.45;
+
+/// How much glass tints what's behind it.
+const GLASS_OPACITY: f32 = 0.3;
+
+/// Darkening per cell of liquid above, and the most it can add up to.
+const DEPTH_SHADE: f32 = 0.025;
+const MAX_DEPTH_SHADE: f32 = 0.35;
+
+/// Darkening per layer of soot on a surface.
+const SOOT_SHADE: f32 = 0.22;
+
+/// Color of live Life cells, and how strongly `Additive` blends it in.
+const LIFE_COLOR: Rgb = [0.45, 1.0, 0.55];
+const LIFE_GLOW: f32 = 0.5;
+
+type Rgb = [f32; 3];
+
+fn base(species: u8) -> Rgb {
+    if (CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).contains(&species) { return custom_color(species); }
+    let c = PALETTE.get(species as usize).copied().unwrap_or(PALETTE[SPECIES_EMPTY as usize]);
+    [c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0]
+}
+
+/// Custom species get their own hue, spread around the color wheel by the
+/// golden ratio so neighboring IDs look nothing alike.
+fn custom_color(species: u8) -> Rgb {
+    let hue = ((species - CUSTOM_SPECIES_FIRST) as f32 * 0.618_034).fract() * 6.0;
+    let ramp = |offset: f32| (((hue + offset) % 6.0 - 3.0).abs() - 1.0).clamp(0.0, 1.0);
+    let rgb = [ramp(0.0), ramp(4.0), ramp(2.0)];
+    mix([0.5, 0.5, 0.5], rgb, 0.6)
+}
+
+fn mix(a: Rgb, b: Rgb, t: f32) -> Rgb {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+fn shift(c: Rgb, d: f32) -> Rgb {
+    [c[0] + d, c[1] + d, c[2] + d]
+}
+
+/// Colors for the worldgen decorations, picked by variant.
+const ORE_COLORS: [Rgb; 3] = [[0.62, 0.34, 0.2], [0.3, 0.58, 0.5], [0.86, 0.7, 0.26]];
+const FLOWER_COLORS: [Rgb; 3] = [[0.92, 0.26, 0.32], [0.96, 0.84, 0.3], [0.72, 0.5, 0.92]];
+const PEBBLE_COLORS: [Rgb; 2] = [[0.5, 0.46, 0.42], [0.64, 0.58, 0.52]];
+
+/// Color of a cell marked by the decoration pass.
+fn decor_color(species: u8, ra: u8) -> Rgb {
+    let v = decor::variant(ra) as usize;
+    match species {
+        SPECIES_STONE => ORE_COLORS[v % ORE_COLORS.len()],
+        SPECIES_PLANT => FLOWER_COLORS[v % FLOWER_COLORS.len()],
+        _ => PEBBLE_COLORS[v % PEBBLE_COLORS.len()],
+    }
+}
+
+/// Cheap per-pixel noise in 0..1 for flicker, stable for a given input.
+fn hash01(x: usize, y: usize, salt: u8) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x9E37_79B9) ^ (y as u32).wrapping_mul(0x85EB_CA6B) ^ salt as u32;
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h & 0xFFFF) as f32 / 65535.0
+}
+
+/// Pale streaks along the way a liquid is moving, stronger the faster it
+/// goes: falling water streaks down its columns, a current along its rows.
+fn flow_streaks(c: Rgb, (fx, fy): (i8, i8), x: usize, y: usize) -> Rgb {
+    let strength = fx.unsigned_abs().max(fy.unsigned_abs()) as f32 / crate::FLOW_MAX as f32;
+    if strength == 0.0 { return c; }
+    let streak = if fy.unsigned_abs() >= fx.unsigned_abs() { hash01(x, 0, 23) } else { hash01(0, y, 23) };
+    shift(c, strength * (streak * 0.12 - 0.03))
+}
+
+/// Soap clouds water a milky pale blue, and the oil it has lifted turns
+/// it murky.
+fn solute_tint(c: Rgb, solute: u8) -> Rgb {
+    match solute {
+        SPECIES_SOAP => mix(c, [0.8, 0.88, 0.95], 0.35),
+        SPECIES_OIL => mix(c, base(SPECIES_OIL), 0.35),
+        _ => c,
+    }
+}
+
+/// Soot darkens a surface a layer at a time; steam beads it with droplets.
+fn coating_tint(c: Rgb, soot: u8, damp: bool, x: usize, y: usize) -> Rgb {
+    let mut c = mix(c, [0.08, 0.07, 0.07], soot as f32 * SOOT_SHADE);
+    if damp && hash01(x, y, 29) < 0.25 { c = mix(c, [0.75, 0.85, 0.95], 0.45); }
+    c
+}
+
+/// Flame color for fire fed by `fuel`, from cool to white-hot with
+/// temperature and jittered by the remaining fuel so flames flicker from
+/// tick to tick. Oil burns a sooty orange, wood a clean yellow, gas blue
+/// and thermite white; anything else (or fire painted by hand) runs deep
+/// red to bright yellow.
+fn flame_color(fuel: u8, ra: u8, temp: u8, x: usize, y: usize) -> Rgb {
+    let flicker = hash01(x, y, ra) * 0.25 - 0.1;
+    let heat = ((temp as f32 - 30.0) / 200.0 + flicker).clamp(0.0, 1.0);
+    match fuel {
+        // Streaked with soot that never quite burns off.
+        SPECIES_OIL => shift(mix([0.3, 0.06, 0.0], [1.0, 0.5, 0.08], heat), -hash01(x, y, ra ^ 0x5a) * 0.3),
+        SPECIES_WOOD | SPECIES_PLANT | SPECIES_CHARCOAL => mix([0.8, 0.35, 0.0], [1.0, 0.95, 0.45], heat),
+        SPECIES_GAS | SPECIES_HYDROGEN => mix([0.1, 0.2, 0.75], [0.55, 0.8, 1.0], heat),
+        SPECIES_THERMITE => mix([1.0, 0.95, 0.85], [1.0, 1.0, 1.0], heat),
+        _ => mix([0.6, 0.1, 0.0], [1.0, 0.9, 0.3], heat),
+    }
+}
+
+/// Color of a single foreground cell. `depth` is the number of cells of
+/// the same liquid directly above it.
+fn cell_color(species: u8, ra: u8, temp: u8, x: usize, y: usize, depth: u32) -> Rgb {
+    let r = ra as f32 / 255.0;
+    let t = temp as f32;
+    let mut c = base(species);
+    match species {
+        SPECIES_STONE | SPECIES_PLANT | SPECIES_SAND if decor::is_decorated(ra) => c = decor_color(species, ra),
+        SPECIES_SAND | SPECIES_DIRT => c = shift(c, r * 0.05 - 0.025),
+        // Lightens back toward dry sand as it dries out.
+        SPECIES_WET_SAND => {
+            let damp = (ra as f32 / crate::WET_SAND_MOISTURE as f32).clamp(0.0, 1.0);
+            c = shift(mix(base(SPECIES_SAND), c, 0.4 + 0.6 * damp), hash01(x, y, 19) * 0.04 - 0.02);
+        }
+        // A laden ant shows a speck of what it carries.
+        SPECIES_ANT if crate::ant_load(ra) != SPECIES_EMPTY => c = mix(c, base(crate::ant_load(ra)), 0.4),
+        SPECIES_FISH if ra & crate::FISH_DEAD != 0 => c = [0.52, 0.5, 0.44],
+        // Wet sheen here and there.
+        SPECIES_MUD => c = shift(c, if hash01(x, y, 17) > 0.93 { 0.06 } else { r * 0.03 - 0.015 }),
+        SPECIES_WATER | SPECIES_SALTWATER => {
+            c[2] += r * 0.06 - 0.03;
+            c[1] += r * 0.02 - 0.01;
+        }
+        SPECIES_OIL | SPECIES_ICE | SPECIES_WOOD | SPECIES_WAX | SPECIES_MOLTEN_WAX | SPECIES_NITRO => c = shift(c, r * 0.03 - 0.015),
+        SPECIES_FIRE => c = flame_color(SPECIES_EMPTY, ra, temp, x, y),
+        // Blue-white, flickering as the bolt fades.
+        SPECIES_LIGHTNING => c = mix([0.55, 0.6, 1.0], c, 0.6 + 0.4 * hash01(x, y, ra)),
+        // Dims from bright orange to dull red as it burns out.
+        SPECIES_EMBER => c = mix([0.5, 0.08, 0.02], c, (ra as f32 / crate::EMBER_LIFE_MAX as f32).clamp(0.0, 1.0)),
+        SPECIES_PLANT => {
+            let n = hash01(x, y, 5);
+            c[1] += n * 0.06 - 0.03;
+            c[0] += n * 0.02 - 0.01;
+            match crate::plant_stage(ra) {
+                // Fresh shoots are a paler green.
+                crate::PLANT_SPROUT => c = shift(c, 0.06),
+                crate::PLANT_FLOWERING if hash01(x, y, 11) > 0.8 => {
+                    c = FLOWER_COLORS[(hash01(x, y, 13) * FLOWER_COLORS.len() as f32) as usize % FLOWER_COLORS.len()];
+                }
+                crate::PLANT_WITHERING => c = mix(c, [0.55, 0.45, 0.22], 0.6),
+                _ => {}
+            }
+        }
+        SPECIES_STONE => c = shift(c, r * 0.04 - 0.02),
+        // Chunky: each pebble is a shade lighter or darker than its neighbors.
+        SPECIES_GRAVEL => c = shift(c, hash01(x, y, 9) * 0.14 - 0.07),
+        // The odd bright streak where light catches the pane.
+        SPECIES_GLASS => c = shift(c, if hash01(x, y, 3) > 0.94 { 0.15 } else { 0.0 }),
+        // Steam keeps its pressure in `ra`; bottled-up steam looks thicker.
+        SPECIES_STEAM => c = shift(c, hash01(x, y, 7) * 0.04 - 0.02 - r * 0.12),
+        SPECIES_LAVA => {
+            let heat = ((t - 95.0) / 160.0).clamp(0.0, 1.0);
+            c = mix([0.3, 0.05, 0.0], [1.0, 0.4, 0.1], heat);
+            c[0] += r * 0.04;
+            c[1] += r * 0.06 - 0.02;
+        }
+        SPECIES_SMOKE => {
+            // Hotter smoke is lighter
+            c = shift(c, ((t - 14.0) / 200.0).clamp(0.0, 0.3));
+            c = shift(c, r * 0.04 - 0.02);
+        }
+        // Glows in specks, and dulls toward lead as it decays.
+        SPECIES_URANIUM => {
+            let spent = ra as f32 / crate::URANIUM_SPENT as f32;
+            c = shift(mix(c, base(SPECIES_LEAD), 0.6 * spent), if hash01(x, y, 37) > 0.85 { 0.08 } else { -0.02 });
+        }
+        // Faint, and fainter as it fades.
+        SPECIES_RADIATION => c = mix(base(SPECIES_EMPTY), c, 0.15 + 0.35 * ra as f32 / crate::RADIATION_LIFE as f32),
+        // `ra` counts down the doses left, so the grain comes from position.
+        SPECIES_SOAP => c = shift(c, hash01(x, y, 31) * 0.04 - 0.02),
+        // Lumpy, with the odd bubble catching the light.
+        SPECIES_FOAM => c = shift(c, if hash01(x, y, 29) > 0.9 { 0.05 } else { r * 0.06 - 0.04 }),
+        // Scattered flakes catch the light.
+        SPECIES_SNOW => c = shift(c, if hash01(x, y, 0) > 0.92 { 0.04 } else { -0.03 }),
+        // Thin enough to see the background through.
+        SPECIES_GAS => c = mix(base(SPECIES_EMPTY), shift(c, r * 0.06 - 0.03), 0.5),
+        // Barely there.
+        SPECIES_HYDROGEN | SPECIES_OXYGEN => c = mix(base(SPECIES_EMPTY), shift(c, r * 0.06 - 0.03), 0.25),
+        // Fades out as it thins.
+        SPECIES_TOXIC_GAS => c = mix(base(SPECIES_EMPTY), c, 0.3 + 0.4 * ra as f32 / crate::TOXIC_GAS_LIFE_MAX as f32),
+        SPECIES_ACID => {
+            c[1] += r * 0.04 - 0.02;
+            c[2] += r * 0.02 - 0.01;
+        }
+        SPECIES_VIRUS => {
+            // Starving virus fades toward gray-violet.
+            c = mix([0.42, 0.36, 0.45], c, ra as f32 / crate::VIRUS_LIFE as f32);
+        }
+        // Mirror-bright, with glints that shift as it flows.
+        SPECIES_MERCURY => {
+            c = shift(c, if hash01(x, y, ra & !electricity::CHARGED) > 0.9 { 0.08 } else { r * 0.06 - 0.05 });
+            if electricity::is_charged(ra) { c = mix(c, [0.75, 0.9, 1.0], 0.4); }
+        }
+        // A lit fuse glows at its burning point; burning thermite is white hot.
+        SPECIES_FUSE if ra > 0 => c = [1.0, 0.55, 0.15],
+        SPECIES_FUSE => c = shift(c, if (x + y).is_multiple_of(3) { 0.04 } else { 0.0 }),
+        SPECIES_THERMITE if ra > 0 => c = shift([1.0, 0.97, 0.85], hash01(x, y, ra) * 0.1 - 0.1),
+        SPECIES_THERMITE => c = shift(c, hash01(x, y, 5) * 0.06 - 0.03),
+        SPECIES_METAL => {
+            c = shift(c, r * 0.04 - 0.02);
+            if electricity::is_charged(ra) { c = mix(c, [0.75, 0.9, 1.0], 0.5); }
+        }
+        SPECIES_SWITCH => {
+            if ra & electricity::SWITCH_CLOSED != 0 { c = [0.35, 0.7, 0.4]; }
+            if electricity::is_charged(ra) { c = mix(c, [0.75, 0.9, 1.0], 0.3); }
+        }
+        // A primed clone block takes on a tint of what it's copying.
+        SPECIES_CLONE if ra != SPECIES_EMPTY => c = mix(c, base(ra), 0.35),
+        SPECIES_EMPTY | SPECIES_WALL | SPECIES_CLONE | SPECIES_VOID | SPECIES_HEATER | SPECIES_COOLER => {}
+        _ => c = shift(c, r * 0.04 - 0.02),
+    }
+
+    if surfaces::is_liquid(species) && species != SPECIES_LAVA {
+        c = shift(c, -(depth as f32 * DEPTH_SHADE).min(MAX_DEPTH_SHADE));
+    }
+
+    // Temperature glow for everything that doesn't already color by heat
+    if !matches!(species, SPECIES_EMPTY | SPECIES_WALL | SPECIES_FIRE | SPECIES_LAVA | SPECIES_EMBER | SPECIES_THERMITE | SPECIES_LIGHTNING) && t > 40.0 {
+        let glow = ((t - 40.0) / 180.0).clamp(0.0, 0.6);
+        c = mix(c, [1.0, 0.3, 0.05], glow);
+    }
+    c
+}
+
+/// Brightness of an unlit scene at the dead of night.
+const NIGHT_BRIGHTNESS: f32 = 0.3;
+
+/// Cells that give off their own light and so stay bright at night.
+fn is_emissive(species: u8, ra: u8) -> bool {
+    match species {
+        SPECIES_FIRE | SPECIES_LAVA | SPECIES_EMBER | SPECIES_LIGHTNING | SPECIES_URANIUM | SPECIES_RADIATION => true,
+        SPECIES_FUSE | SPECIES_THERMITE => ra > 0,
+        _ => false,
+    }
+}
+
+/// Writes `width * height` RGBA pixels into `out`, row-major with alpha
+/// 255. Empty cells show the background layer, if any, dimmed. `light` is
+/// the daylight level, 0.0..=1.0; below full daylight everything but
+/// glowing cells darkens. Live cells of the Life layer, if any, are drawn
+/// over everything as its blend mode says, and glow at night. Stops early
+/// if `out` is too short.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render(
+    cells: &[u8], background: Option<&Background>, life: Option<&LifeLayer>,
+    width: usize, height: usize, light: f32, out: &mut [u8],
+) {
+    render_rect(cells, background, life, width, 0, 0, width, height, light, out);
+}
+
+/// Renders the `rect_w` x `rect_h` block of cells at (x0, y0) into `out`
+/// as a tightly packed RGBA image of that size. The block must lie inside
+/// the grid.
+#[allow(clippy::too_many_arguments)]
+fn render_rect(
+    cells: &[u8], background: Option<&Background>, life: Option<&LifeLayer>, width: usize,
+    x0: usize, y0: usize, rect_w: usize, rect_h: usize, light: f32, out: &mut [u8],
+) {
+    let life = life.filter(|l| l.blend != LifeBlend::Hidden);
+    let empty = base(SPECIES_EMPTY);
+    let brightness = NIGHT_BRIGHTNESS + (1.0 - NIGHT_BRIGHTNESS) * light.clamp(0.0, 1.0);
+    // Liquid depth runs start above the rect, but shading saturates, so
+    // there's no need to look further up than that.
+    let max_run = (MAX_DEPTH_SHADE / DEPTH_SHADE).ceil() as usize;
+    let mut depth: Vec<u32> = (x0..x0 + rect_w)
+        .map(|x| {
+            let species_at = |y: usize| cells[(y * width + x) * CELL_STRIDE];
+            let mut run = 0;
+            if y0 > 0 {
+                let top = species_at(y0 - 1);
+                while run < max_run && run < y0 && species_at(y0 - 1 - run) == top { run += 1; }
+            }
+            run as u32
+        })
+        .collect();
+
+    for ry in 0..rect_h {
+        let y = y0 + ry;
+        for (rx, run) in depth.iter_mut().enumerate() {
+            let x = x0 + rx;
+            let p = (ry * rect_w + rx) * RGBA_STRIDE;
+            if p + RGBA_STRIDE > out.len() { return; }
+            let i = (y * width + x) * CELL_STRIDE;
+            let species = cells[i];
+
+            let above = if y > 0 { cells[i - width * CELL_STRIDE] } else { SPECIES_EMPTY };
+            // `run` holds the run length ending at the cell above; it
+            // counts toward this cell only if the species matches.
+            let d = if y > 0 && above == species { *run } else { 0 };
+            *run = d + 1;
+
+            let mut c = if species == SPECIES_FIRE {
+                flame_color(crate::fire_origin(cells, i), cells[i + 1], cells[i + 2], x, y)
+            } else {
+                cell_color(species, cells[i + 1], cells[i + 2], x, y, d)
+            };
+            if surfaces::is_liquid(species) { c = flow_streaks(c, crate::flow(cells, i), x, y); }
+            if species == SPECIES_WATER { c = solute_tint(c, crate::solute(cells, i)); }
+            if crate::takes_coating(species) { c = coating_tint(c, crate::soot(cells, i), crate::is_damp(cells, i), x, y); }
+            if matches!(species, SPECIES_EMPTY | SPECIES_GLASS) {
+                let mut backdrop = empty;
+                if let Some(bg) = background {
+                    let b = (y * width + x) * BG_STRIDE;
+                    let bg_species = bg.cells[b];
+                    if bg_species != SPECIES_EMPTY {
+                        let behind = cell_color(bg_species, 0, bg.cells[b + 1], x, y, 0);
+                        backdrop = mix(empty, behind, BG_SHADE);
+                    }
+                }
+                // Glass tints whatever is behind it rather than hiding it.
+                c = if species == SPECIES_GLASS { mix(backdrop, c, GLASS_OPACITY) } else { backdrop };
+            }
+            if brightness < 1.0 && !is_emissive(species, cells[i + 1]) {
+                c = c.map(|k| k * brightness);
+            }
+            if let Some(life) = life.filter(|l| l.alive(width, x, y)) {
+                c = match life.blend {
+                    LifeBlend::Additive => [0, 1, 2].map(|k| c[k] + LIFE_COLOR[k] * LIFE_GLOW),
+                    _ => LIFE_COLOR,
+                };
+            }
+
+            for k in 0..3 {
+                out[p + k] = (c[k].clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+            }
+            out[p + 3] = 255;
+        }
+    }
+}
+
+// ── Viewport ──────────────────────────────────────────────────────────
+
+const MIN_ZOOM: f32 = 0.125;
+const MAX_ZOOM: f32 = 64.0;
+
+/// Color of pixels that fall outside the world.
+const OUT_OF_WORLD: [u8; 4] = [0, 0, 0, 255];
+
+/// A camera onto the world: the cell coordinate shown at the top-left
+/// pixel, a zoom in pixels per cell, and the output size in pixels.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    x: f32,
+    y: f32,
+    zoom: f32,
+    width: usize,
+    height: usize,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Viewport {
+    /// A `width` x `height` pixel view at 1 pixel per cell, showing the
+    /// world from its top-left corner.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new(width: usize, height: usize) -> Viewport {
+        Viewport { x: 0.0, y: 0.0, zoom: 1.0, width, height }
+    }
+
+    pub fn x(&self) -> f32 { self.x }
+    pub fn y(&self) -> f32 { self.y }
+    pub fn zoom(&self) -> f32 { self.zoom }
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Moves the view so (x, y) in cell units sits at the top-left pixel.
+    pub fn set_offset(&mut self, x: f32, y: f32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Shifts the view by (dx, dy) screen pixels.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x += dx / self.zoom;
+        self.y += dy / self.zoom;
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the cell under pixel
+    /// (px, py) in place — what a scroll wheel should do.
+    pub fn zoom_at(&mut self, factor: f32, px: f32, py: f32) {
+        let (wx, wy) = (self.world_x(px), self.world_y(py));
+        self.set_zoom(self.zoom * factor);
+        self.x = wx - px / self.zoom;
+        self.y = wy - py / self.zoom;
+    }
+
+    /// Cell-space x coordinate under screen pixel column `px`.
+    pub fn world_x(&self, px: f32) -> f32 { self.x + px / self.zoom }
+
+    /// Cell-space y coordinate under screen pixel row `py`.
+    pub fn world_y(&self, py: f32) -> f32 { self.y + py / self.zoom }
+}
+
+/// Renders what `view` sees into `out` (`view.width * view.height` RGBA
+/// pixels). Only cells inside the view are colored; they're then scaled
+/// nearest-neighbor to the output.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render_view(
+    cells: &[u8], background: Option<&Background>, life: Option<&LifeLayer>, width: usize, height: usize,
+    view: &Viewport, light: f32, out: &mut [u8],
+) {
+    let wx0 = view.x.floor().max(0.0) as usize;
+    let wy0 = view.y.floor().max(0.0) as usize;
+    let wx1 = (view.world_x(view.width as f32).ceil().max(0.0) as usize).min(width);
+    let wy1 = (view.world_y(view.height as f32).ceil().max(0.0) as usize).min(height);
+    let (rect_w, rect_h) = (wx1.saturating_sub(wx0), wy1.saturating_sub(wy0));
+
+    let mut visible = vec![0u8; rect_w * rect_h * RGBA_STRIDE];
+    if rect_w > 0 && rect_h > 0 {
+        render_rect(cells, background, life, width, wx0, wy0, rect_w, rect_h, light, &mut visible);
+    }
+
+    for py in 0..view.height {
+        let wy = view.world_y(py as f32 + 0.5).floor();
+        for px in 0..view.width {
+            let p = (py * view.width + px) * RGBA_STRIDE;
+            if p + RGBA_STRIDE > out.len() { return; }
+            let wx = view.world_x(px as f32 + 0.5).floor();
+            let inside = wx >= wx0 as f32 && wy >= wy0 as f32 && (wx as usize) < wx1 && (wy as usize) < wy1;
+            let pixel = if inside {
+                let v = ((wy as usize - wy0) * rect_w + (wx as usize - wx0)) * RGBA_STRIDE;
+                [visible[v], visible[v + 1], visible[v + 2], visible[v + 3]]
+            } else {
+                OUT_OF_WORLD
+            };
+            out[p..p + RGBA_STRIDE].copy_from_slice(&pixel);
+        }
+    }
+}
+
+/// Heat-map gradient stops: (temperature, color).
+const HEAT_STOPS: [(f32, Rgb); 6] = [
+    (0.0, [0.05, 0.0, 0.25]),
+    (12.0, [0.1, 0.2, 0.8]),
+    (40.0, [0.1, 0.8, 0.6]),
+    (100.0, [0.95, 0.85, 0.1]),
+    (180.0, [1.0, 0.15, 0.05]),
+    (255.0, [1.0, 1.0, 1.0]),
+];
+
+fn heat_color(temp: u8) -> Rgb {
+    let t = temp as f32;
+    for pair in HEAT_STOPS.windows(2) {
+        let (t0, c0) = pair[0];
+        let (t1, c1) = pair[1];
+        if t <= t1 {
+            return mix(c0, c1, (t - t0) / (t1 - t0));
+        }
+    }
+    HEAT_STOPS[HEAT_STOPS.len() - 1].1
+}
+
+/// Like `render`, but colors every cell purely by temperature: deep blue
+/// below ambient, through green and yellow, to red and white-hot.
+pub(crate) fn render_heatmap(cells: &[u8], width: usize, height: usize, out: &mut [u8]) {
+    for i in 0..width * height {
+        let p = i * RGBA_STRIDE;
+        if p + RGBA_STRIDE > out.len() { return; }
+        let c = heat_color(cells[i * CELL_STRIDE + 2]);
+        for k in 0..3 {
+            out[p + k] = (c[k].clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+        }
+        out[p + 3] = 255;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_cell_raw;
+
+    fn pixel(out: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+        let p = (y * width + x) * RGBA_STRIDE;
+        [out[p], out[p + 1], out[p + 2], out[p + 3]]
+    }
+
+    #[test]
+    fn empty_and_wall_use_palette() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_WALL, 0, 0, 0);
+        let mut out = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, None, None, 2, 1, 1.0, &mut out);
+        assert_eq!(pixel(&out, 2, 0, 0), [26, 26, 46, 255]);
+        assert_eq!(pixel(&out, 2, 1, 0), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn custom_species_get_distinct_colors() {
+        let first = base(CUSTOM_SPECIES_FIRST);
+        assert_ne!(first, base(CUSTOM_SPECIES_FIRST + 1));
+        assert_ne!(first, base(SPECIES_EMPTY), "Custom cells mustn't vanish into the background");
+        assert!((CUSTOM_SPECIES_FIRST..=CUSTOM_SPECIES_LAST).all(|s| base(s).iter().all(|c| (0.0..=1.0).contains(c))));
+    }
+
+    #[test]
+    fn hotter_fire_is_brighter() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_FIRE, 10, 40, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_FIRE, 10, 230, 0);
+        let mut out = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, None, None, 2, 1, 1.0, &mut out);
+        assert!(pixel(&out, 2, 1, 0)[1] > pixel(&out, 2, 0, 0)[1]);
+    }
+
+    #[test]
+    fn flames_take_the_color_of_their_fuel() {
+        let fuels = [SPECIES_OIL, SPECIES_WOOD, SPECIES_GAS, SPECIES_THERMITE];
+        let mut cells = vec![0u8; fuels.len() * CELL_STRIDE];
+        for (x, &fuel) in fuels.iter().enumerate() {
+            set_cell_raw(&mut cells, fuels.len(), x, 0, SPECIES_FIRE, 10, 150, 0);
+            crate::set_fire_origin(&mut cells, x * CELL_STRIDE, fuel);
+        }
+        let mut out = vec![0u8; fuels.len() * RGBA_STRIDE];
+        render(&cells, None, None, fuels.len(), 1, 1.0, &mut out);
+        let [oil, wood, gas, thermite] = [0, 1, 2, 3].map(|x| pixel(&out, fuels.len(), x, 0));
+        let sum = |p: [u8; 4]| p[..3].iter().map(|&k| k as u32).sum::<u32>();
+        assert!(sum(oil) < sum(wood), "Oil burns sootier than wood");
+        assert!(gas[2] > gas[0], "Gas burns blue");
+        assert!(thermite[..3].iter().all(|&k| k > 230), "Thermite burns white");
+    }
+
+    #[test]
+    fn deep_water_is_darker() {
+        let mut cells = vec![0u8; 10 * CELL_STRIDE];
+        for y in 0..10 { set_cell_raw(&mut cells, 1, 0, y, SPECIES_WATER, 0, 12, 0); }
+        let mut out = vec![0u8; 10 * RGBA_STRIDE];
+        render(&cells, None, None, 1, 10, 1.0, &mut out);
+        assert!(pixel(&out, 1, 0, 9)[2] < pixel(&out, 1, 0, 0)[2]);
+    }
+
+    #[test]
+    fn background_shows_through_empty_cells_only() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_SAND, 0, 12, 0);
+        let mut bg = Background::new(2, 1);
+        bg.set(2, 0, 0, SPECIES_STONE);
+        bg.set(2, 1, 0, SPECIES_STONE);
+        let mut plain = vec![0u8; 2 * RGBA_STRIDE];
+        let mut backed = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, None, None, 2, 1, 1.0, &mut plain);
+        render(&cells, Some(&bg), None, 2, 1, 1.0, &mut backed);
+        assert_ne!(pixel(&plain, 2, 0, 0), pixel(&backed, 2, 0, 0));
+        assert_eq!(pixel(&plain, 2, 1, 0), pixel(&backed, 2, 1, 0));
+    }
+
+    #[test]
+    fn glass_tints_the_background_instead_of_hiding_it() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_GLASS, 0, 12, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_GLASS, 0, 12, 0);
+        let mut bg = Background::new(2, 1);
+        bg.set(2, 0, 0, SPECIES_LAVA);
+        bg.set(2, 1, 0, SPECIES_WATER);
+        let mut out = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, Some(&bg), None, 2, 1, 1.0, &mut out);
+        let (lava, water) = (pixel(&out, 2, 0, 0), pixel(&out, 2, 1, 0));
+        assert!(lava[0] > water[0] && water[2] > lava[2], "{lava:?} vs {water:?}");
+    }
+
+    #[test]
+    fn heatmap_gradient_is_monotonic_in_redness_up_to_red_hot() {
+        let mut cells = vec![0u8; 4 * CELL_STRIDE];
+        for (x, t) in [0u8, 12, 100, 180].into_iter().enumerate() {
+            set_cell_raw(&mut cells, 4, x, 0, SPECIES_EMPTY, 0, t, 0);
+        }
+        let mut out = vec![0u8; 4 * RGBA_STRIDE];
+        render_heatmap(&cells, 4, 1, &mut out);
+        let reds: Vec<u8> = (0..4).map(|x| pixel(&out, 4, x, 0)[0]).collect();
+        assert!(reds.windows(2).all(|w| w[0] <= w[1]), "reds {:?}", reds);
+        assert!(pixel(&out, 4, 0, 0)[2] > pixel(&out, 4, 0, 0)[0], "Cold is blue");
+        assert_eq!(heat_color(255), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn decorated_cells_stand_out() {
+        let plain = cell_color(SPECIES_STONE, 5, 20, 0, 0, 0);
+        let ore = cell_color(SPECIES_STONE, decor::DECOR_FLAG | 2, 20, 0, 0, 0);
+        assert!((0..3).map(|k| (plain[k] - ore[k]).abs()).sum::<f32>() > 0.3);
+    }
+
+    #[test]
+    fn viewport_zoom_at_keeps_anchor_fixed() {
+        let mut v = Viewport::new(100, 100);
+        v.set_offset(10.0, 20.0);
+        let before = (v.world_x(40.0), v.world_y(60.0));
+        v.zoom_at(4.0, 40.0, 60.0);
+        assert_eq!(v.zoom(), 4.0);
+        assert!((v.world_x(40.0) - before.0).abs() < 1e-4);
+        assert!((v.world_y(60.0) - before.1).abs() < 1e-4);
+        v.set_zoom(1000.0);
+        assert_eq!(v.zoom(), MAX_ZOOM);
+        v.pan(-64.0, 0.0);
+        assert!((v.x() - (before.0 - 40.0 / 4.0 - 1.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn viewport_scales_and_clips() {
+        let mut cells = vec![0u8; 4 * 4 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 4, 2, 1, SPECIES_WALL, 0, 0, 0);
+        let mut full = vec![0u8; 4 * 4 * RGBA_STRIDE];
+        render(&cells, None, None, 4, 4, 1.0, &mut full);
+
+        let mut view = Viewport::new(4, 4);
+        view.set_offset(2.0, 1.0);
+        view.set_zoom(2.0);
+        let mut out = vec![0u8; 4 * 4 * RGBA_STRIDE];
+        render_view(&cells, None, None, 4, 4, &view, 1.0, &mut out);
+        // Wall at (2, 1) fills the top-left 2x2 pixel block.
+        for (px, py) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert_eq!(pixel(&out, 4, px, py), pixel(&full, 4, 2, 1));
+        }
+        assert_eq!(pixel(&out, 4, 2, 0), pixel(&full, 4, 3, 1));
+
+        view.set_offset(3.0, 3.0);
+        render_view(&cells, None, None, 4, 4, &view, 1.0, &mut out);
+        assert_eq!(pixel(&out, 4, 3, 3), OUT_OF_WORLD, "Pixels past the edge are void");
+    }
+
+    #[test]
+    fn partial_render_matches_full_render() {
+        let mut cells = vec![0u8; 3 * 12 * CELL_STRIDE];
+        for y in 0..12 { set_cell_raw(&mut cells, 3, 1, y, SPECIES_WATER, 5, 12, 0); }
+        let mut full = vec![0u8; 3 * 12 * RGBA_STRIDE];
+        render(&cells, None, None, 3, 12, 1.0, &mut full);
+        let mut view = Viewport::new(3, 4);
+        view.set_offset(0.0, 8.0);
+        let mut out = vec![0u8; 3 * 4 * RGBA_STRIDE];
+        render_view(&cells, None, None, 3, 12, &view, 1.0, &mut out);
+        for y in 0..4 {
+            assert_eq!(pixel(&out, 3, 1, y), pixel(&full, 3, 1, y + 8), "Depth shading carries into the view");
+        }
+    }
+
+    #[test]
+    fn night_darkens_all_but_glowing_cells() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_WALL, 0, 0, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_FIRE, 10, 120, 0);
+        let (mut day, mut night) = (vec![0u8; 2 * RGBA_STRIDE], vec![0u8; 2 * RGBA_STRIDE]);
+        render(&cells, None, None, 2, 1, 1.0, &mut day);
+        render(&cells, None, None, 2, 1, 0.0, &mut night);
+        assert_eq!(pixel(&night, 2, 0, 0), [38, 38, 38, 255]);
+        assert_eq!(pixel(&night, 2, 1, 0), pixel(&day, 2, 1, 0), "Fire lights itself");
+    }
+
+    #[test]
+    fn life_layer_draws_by_blend_mode() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_SAND, 0, 12, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_SAND, 0, 12, 0);
+        let mut life = LifeLayer::new(2, 1, crate::LifeRule::CONWAY);
+        life.set(2, 0, 0, true);
+        let mut plain = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, None, None, 2, 1, 1.0, &mut plain);
+        let drawn = |life: &LifeLayer| {
+            let mut out = vec![0u8; 2 * RGBA_STRIDE];
+            render(&cells, None, Some(life), 2, 1, 1.0, &mut out);
+            out
+        };
+
+        life.blend = LifeBlend::Hidden;
+        assert_eq!(drawn(&life), plain);
+        life.blend = LifeBlend::Additive;
+        let added = drawn(&life);
+        assert!(pixel(&added, 2, 0, 0)[1] > pixel(&plain, 2, 0, 0)[1], "Live cells brighten the sand");
+        assert_eq!(pixel(&added, 2, 1, 0), pixel(&plain, 2, 1, 0), "Dead cells leave it alone");
+        life.blend = LifeBlend::Replace;
+        let replaced = drawn(&life);
+        assert_eq!(&pixel(&replaced, 2, 0, 0)[..3], &LIFE_COLOR.map(|k| (k * 255.0 + 0.5) as u8));
+    }
+
+    #[test]
+    fn soot_darkens_a_surface() {
+        let mut cells = vec![0u8; 2 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_WALL, 0, 0, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_WALL, 0, 0, 0x0C);
+        let mut out = vec![0u8; 2 * RGBA_STRIDE];
+        render(&cells, None, None, 2, 1, 1.0, &mut out);
+        assert!(pixel(&out, 2, 1, 0)[0] < pixel(&out, 2, 0, 0)[0] / 2);
+    }
+
+    #[test]
+    fn short_buffer_is_left_partially_filled() {
+        let cells = vec![0u8; 4 * CELL_STRIDE];
+        let mut out = vec![0u8; 6];
+        render(&cells, None, None, 4, 1, 1.0, &mut out);
+        assert_eq!(&out[..4], &[26, 26, 46, 255]);
+        assert_eq!(&out[4..], &[0, 0]);
+    }
+}
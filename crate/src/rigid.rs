@@ -0,0 +1,261 @@
+use crate::chunks::ChunkMap;
+use crate::{cell_idx, is_fluid, CELL_STRIDE, SPECIES_EMPTY, SPECIES_STONE, SPECIES_WOOD};
+
+/// Set in `ra` of a wood or stone cell that belongs to a rigid body. Color
+/// noise and decoration variants stay below it.
+pub(crate) const RIGID: u8 = 0x40;
+
+const NO_BODY: u32 = u32::MAX;
+
+#[inline(always)]
+pub(crate) fn is_rigid(species: u8, ra: u8) -> bool {
+    matches!(species, SPECIES_WOOD | SPECIES_STONE) && ra & RIGID != 0
+}
+
+/// What a falling body can push out of its way: it sinks through liquids
+/// and gases, which flow into the space it leaves behind.
+#[inline(always)]
+fn is_passable(species: u8) -> bool {
+    species == SPECIES_EMPTY || is_fluid(species)
+}
+
+/// Marks or unmarks the whole wood/stone structure 4-connected to (x, y)
+/// as one rigid body. Returns the number of cells changed.
+pub(crate) fn mark(cells: &mut [u8], width: usize, height: usize, x: usize, y: usize, rigid: bool) -> usize {
+    let i = cell_idx(width, x, y);
+    if !matches!(cells[i], SPECIES_WOOD | SPECIES_STONE) || is_rigid(cells[i], cells[i + 1]) == rigid {
+        return 0;
+    }
+    let mut stack = vec![(x, y)];
+    let mut changed = 0;
+    while let Some((x, y)) = stack.pop() {
+        let i = cell_idx(width, x, y);
+        if !matches!(cells[i], SPECIES_WOOD | SPECIES_STONE) || is_rigid(cells[i], cells[i + 1]) == rigid {
+            continue;
+        }
+        if rigid { cells[i + 1] |= RIGID; } else { cells[i + 1] &= !RIGID; }
+        changed += 1;
+        if x > 0 { stack.push((x - 1, y)); }
+        if x + 1 < width { stack.push((x + 1, y)); }
+        if y > 0 { stack.push((x, y - 1)); }
+        if y + 1 < height { stack.push((x, y + 1)); }
+    }
+    changed
+}
+
+struct Body {
+    cells: Vec<(usize, usize)>,
+    bottom: usize,
+}
+
+/// Finds the rigid bodies in the grid and moves each by at most one cell
+/// per tick: straight down when nothing holds it up, or down and outward
+/// when it rests on a ledge with its center of mass hanging past the edge,
+/// so it tips off. Bodies lower down move first, letting stacks fall
+/// together. Scratch space is kept between ticks.
+pub(crate) struct RigidBodies {
+    labels: Vec<u32>,
+}
+
+impl RigidBodies {
+    pub(crate) fn new() -> RigidBodies {
+        RigidBodies { labels: Vec::new() }
+    }
+
+    /// One tick of rigid motion. Returns false once no rigid cells are left.
+    pub(crate) fn step(&mut self, cells: &mut [u8], width: usize, height: usize, chunks: &mut ChunkMap) -> bool {
+        let mut bodies = self.find_bodies(cells, width, height);
+        if bodies.is_empty() { return false; }
+        bodies.sort_by_key(|b| std::cmp::Reverse(b.bottom));
+        for (id, body) in bodies.iter().enumerate() {
+            // Sorting reordered the bodies; relabel so the checks below see
+            // each body's own cells.
+            for &(x, y) in &body.cells { self.labels[y * width + x] = id as u32; }
+        }
+        for (id, body) in bodies.iter_mut().enumerate() {
+            let id = id as u32;
+            let moved = self.try_move(cells, width, height, body, id, 0, 1)
+                || match self.overhang(cells, width, height, body, id) {
+                    Some(dx) => self.try_move(cells, width, height, body, id, dx, 1),
+                    None => false,
+                };
+            if moved {
+                let (x0, x1) = body.cells.iter().fold((usize::MAX, 0), |(lo, hi), &(x, _)| (lo.min(x), hi.max(x)));
+                let y0 = body.cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+                let r = (x1 - x0).max(body.bottom - y0) / 2 + 2;
+                chunks.wake_area((x0 + x1) / 2, (y0 + body.bottom) / 2, r);
+            }
+        }
+        true
+    }
+
+    fn find_bodies(&mut self, cells: &[u8], width: usize, height: usize) -> Vec<Body> {
+        self.labels.clear();
+        self.labels.resize(width * height, NO_BODY);
+        let mut bodies = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..width * height {
+            let i = start * CELL_STRIDE;
+            if self.labels[start] != NO_BODY || !is_rigid(cells[i], cells[i + 1]) { continue; }
+            let id = bodies.len() as u32;
+            let mut body = Body { cells: Vec::new(), bottom: 0 };
+            self.labels[start] = id;
+            stack.push(start);
+            while let Some(cell) = stack.pop() {
+                let (x, y) = (cell % width, cell / width);
+                body.cells.push((x, y));
+                body.bottom = body.bottom.max(y);
+                let mut visit = |n: usize| {
+                    let j = n * CELL_STRIDE;
+                    if self.labels[n] == NO_BODY && is_rigid(cells[j], cells[j + 1]) {
+                        self.labels[n] = id;
+                        stack.push(n);
+                    }
+                };
+                if x > 0 { visit(cell - 1); }
+                if x + 1 < width { visit(cell + 1); }
+                if y > 0 { visit(cell - width); }
+                if y + 1 < height { visit(cell + width); }
+            }
+            bodies.push(body);
+        }
+        bodies
+    }
+
+    /// When the body rests only on cells to one side of its center of
+    /// mass, the direction (-1 or 1) it would tip toward.
+    fn overhang(&self, cells: &[u8], width: usize, height: usize, body: &Body, id: u32) -> Option<isize> {
+        let (mut lo, mut hi) = (usize::MAX, 0);
+        for &(x, y) in &body.cells {
+            if y + 1 >= height { return None; }
+            let below = (y + 1) * width + x;
+            if self.labels[below] != id && !is_passable(cells[below * CELL_STRIDE]) {
+                lo = lo.min(x);
+                hi = hi.max(x);
+            }
+        }
+        let n = body.cells.len() as f32;
+        let center = body.cells.iter().map(|&(x, _)| x as f32).sum::<f32>() / n;
+        if center < lo as f32 - 0.5 {
+            Some(-1)
+        } else if center > hi as f32 + 0.5 {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// Shifts the whole body by (dx, dy) if every cell it would cover is
+    /// passable or its own; whatever it displaces fills the cells it left.
+    #[allow(clippy::too_many_arguments)]
+    fn try_move(
+        &mut self, cells: &mut [u8], width: usize, height: usize,
+        body: &mut Body, id: u32, dx: isize, dy: usize,
+    ) -> bool {
+        let mut targets = Vec::with_capacity(body.cells.len());
+        for &(x, y) in &body.cells {
+            let nx = x as isize + dx;
+            let ny = y + dy;
+            if nx < 0 || nx as usize >= width || ny >= height { return false; }
+            let n = ny * width + nx as usize;
+            if self.labels[n] != id && !is_passable(cells[n * CELL_STRIDE]) { return false; }
+            targets.push((nx as usize, ny));
+        }
+
+        let take = |cells: &[u8], x: usize, y: usize| -> [u8; CELL_STRIDE] {
+            let i = cell_idx(width, x, y);
+            cells[i..i + CELL_STRIDE].try_into().unwrap()
+        };
+        let solid: Vec<[u8; CELL_STRIDE]> = body.cells.iter().map(|&(x, y)| take(cells, x, y)).collect();
+        let displaced: Vec<[u8; CELL_STRIDE]> = targets
+            .iter()
+            .filter(|&&(x, y)| self.labels[y * width + x] != id)
+            .map(|&(x, y)| take(cells, x, y))
+            .collect();
+
+        for &(x, y) in &body.cells { self.labels[y * width + x] = NO_BODY; }
+        for &(x, y) in &targets { self.labels[y * width + x] = id; }
+        // Cells the body no longer covers take what it pushed aside, topmost
+        // vacated cell first so liquids end up above the body.
+        let mut vacated: Vec<(usize, usize)> =
+            body.cells.iter().copied().filter(|&(x, y)| self.labels[y * width + x] != id).collect();
+        vacated.sort_by_key(|&(x, y)| (y, x));
+        for (&(x, y), c) in vacated.iter().zip(&displaced) {
+            let i = cell_idx(width, x, y);
+            cells[i..i + CELL_STRIDE].copy_from_slice(c);
+        }
+        for (&(x, y), c) in targets.iter().zip(&solid) {
+            let i = cell_idx(width, x, y);
+            cells[i..i + CELL_STRIDE].copy_from_slice(c);
+        }
+        body.cells = targets;
+        body.bottom += dy;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_species, set_cell_raw, SPECIES_WALL, SPECIES_WATER};
+
+    fn grid(width: usize, height: usize) -> (Vec<u8>, ChunkMap) {
+        (vec![0u8; width * height * CELL_STRIDE], ChunkMap::new(width, height))
+    }
+
+    #[test]
+    fn plank_falls_as_one_piece() {
+        let (mut cells, mut chunks) = grid(10, 10);
+        for x in 2..8 { set_cell_raw(&mut cells, 10, x, 2, SPECIES_WOOD, RIGID, 12, 0); }
+        let mut rigid = RigidBodies::new();
+        for _ in 0..3 { assert!(rigid.step(&mut cells, 10, 10, &mut chunks)); }
+        for x in 2..8 {
+            assert_eq!(get_species(&cells, 10, x, 5), SPECIES_WOOD);
+            assert_eq!(get_species(&cells, 10, x, 2), SPECIES_EMPTY);
+        }
+        for _ in 0..10 { rigid.step(&mut cells, 10, 10, &mut chunks); }
+        assert!((2..8).all(|x| get_species(&cells, 10, x, 9) == SPECIES_WOOD), "Settles on the floor");
+    }
+
+    #[test]
+    fn bridge_held_at_one_end_stays_up() {
+        let (mut cells, mut chunks) = grid(10, 10);
+        set_cell_raw(&mut cells, 10, 2, 5, SPECIES_WALL, 0, 0, 0);
+        for x in 1..5 { set_cell_raw(&mut cells, 10, x, 4, SPECIES_STONE, RIGID, 12, 0); }
+        let mut rigid = RigidBodies::new();
+        rigid.step(&mut cells, 10, 10, &mut chunks);
+        assert!((1..5).all(|x| get_species(&cells, 10, x, 4) == SPECIES_STONE));
+    }
+
+    #[test]
+    fn overhanging_body_tips_off_its_ledge() {
+        let (mut cells, mut chunks) = grid(12, 10);
+        set_cell_raw(&mut cells, 12, 2, 5, SPECIES_WALL, 0, 0, 0);
+        for x in 2..8 { set_cell_raw(&mut cells, 12, x, 4, SPECIES_WOOD, RIGID, 12, 0); }
+        let mut rigid = RigidBodies::new();
+        rigid.step(&mut cells, 12, 10, &mut chunks);
+        assert!((3..9).all(|x| get_species(&cells, 12, x, 5) == SPECIES_WOOD));
+    }
+
+    #[test]
+    fn sinks_through_water_and_lifts_it() {
+        let (mut cells, mut chunks) = grid(4, 4);
+        for x in 0..4 { set_cell_raw(&mut cells, 4, x, 3, SPECIES_WATER, 0, 12, 0); }
+        for x in 0..4 { set_cell_raw(&mut cells, 4, x, 2, SPECIES_STONE, RIGID, 12, 0); }
+        RigidBodies::new().step(&mut cells, 4, 4, &mut chunks);
+        assert!((0..4).all(|x| get_species(&cells, 4, x, 3) == SPECIES_STONE));
+        assert!((0..4).all(|x| get_species(&cells, 4, x, 2) == SPECIES_WATER));
+    }
+
+    #[test]
+    fn marking_covers_the_connected_structure() {
+        let mut cells = vec![0u8; 6 * 3 * CELL_STRIDE];
+        for x in 0..3 { set_cell_raw(&mut cells, 6, x, 1, SPECIES_WOOD, 5, 12, 0); }
+        set_cell_raw(&mut cells, 6, 1, 0, SPECIES_STONE, 5, 12, 0);
+        set_cell_raw(&mut cells, 6, 5, 1, SPECIES_WOOD, 5, 12, 0);
+        assert_eq!(mark(&mut cells, 6, 3, 0, 1, true), 4);
+        assert!(!is_rigid(SPECIES_WOOD, cells[cell_idx(6, 5, 1) + 1]), "Separate plank isn't touched");
+        assert_eq!(cells[cell_idx(6, 1, 0) + 1], 5 | RIGID, "Color noise survives");
+        assert_eq!(mark(&mut cells, 6, 3, 2, 1, false), 4);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::background::BG_STRIDE;
+use crate::custom::is_species_id;
+use crate::{initial_state, CELL_STRIDE};
+
+/// Leads every encoded scene, followed by the format version.
+const MAGIC: &[u8; 4] = b"SRLE";
+const VERSION: u8 = 1;
+/// Version of scenes that carry a background layer after the grid.
+const VERSION_BACKGROUND: u8 = 2;
+
+/// Largest world `decode` will allocate, so a corrupt header can't ask for
+/// gigabytes.
+const MAX_CELLS: usize = 1 << 24;
+
+/// Compact scene format for storage and sharing: a header, then the grid
+/// in row-major order as runs of identical (species, temperature) pairs.
+/// Version 2 follows the grid with the background layer, run-length
+/// encoded the same way.
+///
+/// ```text
+/// "SRLE" version:u8 width:varint height:varint
+/// { length:varint species:u8 temperature:u8 }*
+/// [ { length:varint species:u8 temperature:u8 }* ]   version 2 only
+/// ```
+///
+/// Varints are unsigned LEB128. `ra` and the clock aren't stored: color
+/// noise is rerolled on load, and per-cell state kept in `ra` (fuel, fall
+/// speed, a clone's species, switch positions) starts fresh. Scenes
+/// without a background are written as version 1.
+pub(crate) fn encode(cells: &[u8], background: Option<&[u8]>, width: usize, height: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16);
+    out.extend_from_slice(MAGIC);
+    out.push(if background.is_some() { VERSION_BACKGROUND } else { VERSION });
+    write_varint(&mut out, width);
+    write_varint(&mut out, height);
+    write_runs(&mut out, cells.chunks_exact(CELL_STRIDE).map(|c| (c[0], c[2])));
+    if let Some(bg) = background {
+        write_runs(&mut out, bg.chunks_exact(BG_STRIDE).map(|c| (c[0], c[1])));
+    }
+    out
+}
+
+/// A scene parsed by `decode`: the grid, and the background layer if the
+/// scene has one.
+pub(crate) struct Decoded {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) cells: Vec<u8>,
+    pub(crate) background: Option<Vec<u8>>,
+}
+
+/// Parses `encode`'s output.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Decoded, String> {
+    let rest = bytes.strip_prefix(MAGIC.as_slice()).ok_or("not an RLE scene")?;
+    let (&version, mut rest) = rest.split_first().ok_or("truncated header")?;
+    if version != VERSION && version != VERSION_BACKGROUND {
+        return Err(format!("unsupported RLE version {version}"));
+    }
+    let width = read_varint(&mut rest)?;
+    let height = read_varint(&mut rest)?;
+    let total = width.checked_mul(height).filter(|&n| n <= MAX_CELLS).ok_or("world too large")?;
+
+    let mut cells = vec![0; total * CELL_STRIDE];
+    read_runs(&mut rest, &mut cells, CELL_STRIDE, |c, species, temp| {
+        c[0] = species;
+        c[1] = initial_state(species).0;
+        c[2] = temp;
+    })?;
+    let background = if version == VERSION_BACKGROUND {
+        let mut bg = vec![0; total * BG_STRIDE];
+        read_runs(&mut rest, &mut bg, BG_STRIDE, |c, species, temp| {
+            c[0] = species;
+            c[1] = temp;
+        })?;
+        Some(bg)
+    } else {
+        None
+    };
+    if !rest.is_empty() { return Err("runs don't match the world size".into()); }
+    Ok(Decoded { width, height, cells, background })
+}
+
+fn write_runs(out: &mut Vec<u8>, mut cells: impl Iterator<Item = (u8, u8)>) {
+    let Some(mut run) = cells.next() else { return; };
+    let mut len = 1;
+    for cell in cells {
+        if cell == run {
+            len += 1;
+            continue;
+        }
+        write_run(out, len, run);
+        run = cell;
+        len = 1;
+    }
+    write_run(out, len, run);
+}
+
+/// Reads runs off the front of `rest` until every `stride`-byte cell of
+/// `grid` is filled, handing each cell to `fill` with its species and
+/// temperature.
+fn read_runs(
+    rest: &mut &[u8], grid: &mut [u8], stride: usize, fill: impl Fn(&mut [u8], u8, u8),
+) -> Result<(), String> {
+    let total = grid.len() / stride;
+    let mut filled = 0;
+    while filled < total {
+        let len = read_varint(rest)?;
+        let [species, temp, ref tail @ ..] = **rest else { return Err("truncated run".into()); };
+        *rest = tail;
+        if !is_species_id(species) { return Err(format!("unknown species {species}")); }
+        if len == 0 || len > total - filled { return Err("runs don't match the world size".into()); }
+        for c in grid[filled * stride..(filled + len) * stride].chunks_exact_mut(stride) {
+            fill(c, species, temp);
+        }
+        filled += len;
+    }
+    Ok(())
+}
+
+fn write_run(out: &mut Vec<u8>, len: usize, (species, temp): (u8, u8)) {
+    write_varint(out, len);
+    out.push(species);
+    out.push(temp);
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut v: usize) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+pub(crate) fn read_varint(bytes: &mut &[u8]) -> Result<usize, String> {
+    let mut v = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&b, rest) = bytes.split_first().ok_or("truncated varint")?;
+        *bytes = rest;
+        v |= ((b & 0x7F) as usize) << shift;
+        if b & 0x80 == 0 { return Ok(v); }
+    }
+    Err("varint too long".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, SPECIES_COUNT, SPECIES_SAND, SPECIES_WALL, SPECIES_WATER};
+
+    #[test]
+    fn varints_round_trip() {
+        for v in [0, 1, 127, 128, 300, 16_383, 16_384, MAX_CELLS] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, v);
+            let mut rest = buf.as_slice();
+            assert_eq!(read_varint(&mut rest), Ok(v));
+            assert!(rest.is_empty());
+        }
+    }
+
+    #[test]
+    fn runs_collapse_and_keep_temperature() {
+        let mut cells = vec![0u8; 10 * 4 * CELL_STRIDE];
+        for x in 0..10 { set_cell_raw(&mut cells, 10, x, 3, SPECIES_WALL, 0, 0, 0); }
+        set_cell_raw(&mut cells, 10, 4, 2, SPECIES_WATER, 7, 90, 1);
+        let bytes = encode(&cells, None, 10, 4);
+        // Header, then empty / water / empty / wall.
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 1 + 4 * 3);
+
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!((decoded.width, decoded.height), (10, 4));
+        assert!(decoded.background.is_none());
+        for (a, b) in cells.chunks_exact(CELL_STRIDE).zip(decoded.cells.chunks_exact(CELL_STRIDE)) {
+            assert_eq!((a[0], a[2]), (b[0], b[2]));
+        }
+    }
+
+    #[test]
+    fn background_follows_the_grid() {
+        let cells = vec![0u8; 4 * 2 * CELL_STRIDE];
+        let mut bg = vec![0u8; 4 * 2 * BG_STRIDE];
+        for x in 0..4 { bg[(4 + x) * BG_STRIDE..][..2].copy_from_slice(&[SPECIES_WALL, 30]); }
+        let bytes = encode(&cells, Some(&bg), 4, 2);
+        assert_eq!(bytes[4], VERSION_BACKGROUND);
+        // Header, one empty run for the grid, then empty / wall behind it.
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 1 + 3 + 3 * 2);
+        assert_eq!(decode(&bytes).unwrap().background, Some(bg));
+        assert!(decode(&bytes[..bytes.len() - 3]).is_err(), "Background cut short");
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let mut cells = vec![0u8; 3 * 3 * CELL_STRIDE];
+        set_cell_raw(&mut cells, 3, 1, 1, SPECIES_SAND, 0, 0, 0);
+        let bytes = encode(&cells, None, 3, 3);
+        assert!(decode(&bytes).is_ok());
+        assert!(decode(b"PNG!").is_err());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err(), "Truncated");
+
+        let mut short = bytes.clone();
+        short.truncate(bytes.len() - 3);
+        assert!(decode(&short).is_err(), "Missing the last run");
+
+        let mut alien = bytes.clone();
+        let last = alien.len() - 2;
+        alien[last] = SPECIES_COUNT as u8;
+        assert!(decode(&alien).is_err(), "Unknown species");
+
+        let mut huge = Vec::from(MAGIC.as_slice());
+        huge.push(VERSION);
+        write_varint(&mut huge, 1 << 20);
+        write_varint(&mut huge, 1 << 20);
+        assert!(decode(&huge).is_err());
+    }
+}
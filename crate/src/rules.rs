@@ -0,0 +1,167 @@
+use crate::chunks::ChunkMap;
+use crate::custom::{is_species_id, CUSTOM_SPECIES_LAST};
+use crate::events::{Event, EventSink};
+use crate::reactions::release_heat;
+use crate::{cell_idx, in_bounds, initial_state, is_pinned, rand, set_cell_raw, CELL_CLOCK, CLOCK_PARITY_MASK};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// A reaction loaded as data rather than written into the simulation: a
+/// `reactant` cell touching an `agent` cell (orthogonally) turns into
+/// `product`, and the agent into `agent_product`, with chance
+/// `probability` per tick. `heat` is released at the reactant as for the
+/// built-in reactions: positive warms, negative cools.
+///
+/// Products start as if placed by hand, at their usual temperature. A rule
+/// whose product is its reactant leaves that cell alone, so an agent can
+/// act as a catalyst and the other way round.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReactionRule {
+    pub reactant: u8,
+    pub agent: u8,
+    pub product: u8,
+    pub agent_product: u8,
+    pub probability: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub heat: i8,
+}
+
+impl ReactionRule {
+    fn validate(&self) -> Result<(), String> {
+        for s in [self.reactant, self.agent, self.product, self.agent_product] {
+            if !is_species_id(s) { return Err(format!("unknown species {s}")); }
+        }
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err(format!("probability {} is outside 0..=1", self.probability));
+        }
+        Ok(())
+    }
+}
+
+/// The data-driven rules a world runs, evaluated once per tick over the
+/// awake part of the grid, after phase transitions.
+#[derive(Clone, Default)]
+pub(crate) struct RuleSet {
+    rules: Vec<ReactionRule>,
+    /// Indices into `rules`, by reactant species.
+    by_reactant: Vec<Vec<usize>>,
+}
+
+impl RuleSet {
+    pub(crate) fn rules(&self) -> &[ReactionRule] { &self.rules }
+
+    pub(crate) fn add(&mut self, rule: ReactionRule) -> Result<(), String> {
+        rule.validate()?;
+        if self.by_reactant.is_empty() { self.by_reactant = vec![Vec::new(); CUSTOM_SPECIES_LAST as usize + 1]; }
+        self.by_reactant[rule.reactant as usize].push(self.rules.len());
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Runs every rule once over the cells in active chunks. A reactant
+    /// with an agent next to it keeps its chunk awake until the rule fires,
+    /// however long the odds.
+    pub(crate) fn apply(
+        &self, cells: &mut [u8], width: usize, height: usize, chunks: &mut ChunkMap, events: &mut EventSink,
+    ) {
+        for y in 0..height {
+            let spans: Vec<(usize, usize)> = chunks.active_spans(y, width).collect();
+            for x in spans.into_iter().flat_map(|(x0, x1)| x0..x1) {
+                if !chunks.is_live(x, y) { continue; }
+                let i = cell_idx(width, x, y);
+                if is_pinned(cells, i) { continue; }
+                let species = cells[i];
+                let Some(candidates) = self.by_reactant.get(species as usize) else { continue; };
+                for &r in candidates {
+                    let rule = &self.rules[r];
+                    if rule.probability <= 0.0 { continue; }
+                    let Some((ax, ay)) = find_agent(cells, width, height, x, y, rule.agent) else { continue; };
+                    if rand() >= rule.probability as f64 {
+                        chunks.wake_area(x, y, 0);
+                        continue;
+                    }
+                    if rule.product != rule.reactant { transmute(cells, width, x, y, rule.product); }
+                    if rule.agent_product != rule.agent { transmute(cells, width, ax, ay, rule.agent_product); }
+                    release_heat(cells, width, height, x, y, rule.heat);
+                    chunks.wake_area(x, y, 2);
+                    chunks.mark_unsettled(x, y);
+                    events.push(Event::Reacted { x, y, species, with: rule.agent });
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The first orthogonal neighbor of (x, y) holding `agent`, starting from
+/// a random side so no direction is favored.
+fn find_agent(cells: &[u8], width: usize, height: usize, x: usize, y: usize, agent: u8) -> Option<(usize, usize)> {
+    const SIDES: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+    let start = (rand() * 4.0) as usize;
+    (0..4).map(|k| SIDES[(start + k) % 4]).find_map(|(dx, dy)| {
+        let (nx, ny) = (x as isize + dx, y as isize + dy);
+        if !in_bounds(width, height, nx, ny) { return None; }
+        let (nx, ny) = (nx as usize, ny as usize);
+        let i = cell_idx(width, nx, ny);
+        (cells[i] == agent && !is_pinned(cells, i)).then_some((nx, ny))
+    })
+}
+
+/// Replaces the cell at (x, y) with a fresh `species`, keeping its parity.
+fn transmute(cells: &mut [u8], width: usize, x: usize, y: usize, species: u8) {
+    let (ra, temp) = initial_state(species);
+    let clock = cells[cell_idx(width, x, y) + CELL_CLOCK] & CLOCK_PARITY_MASK;
+    set_cell_raw(cells, width, x, y, species, ra, temp, clock);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_ACID, SPECIES_COUNT, SPECIES_EMPTY, SPECIES_SAND, SPECIES_STONE, SPECIES_WALL, SPECIES_WATER};
+
+    fn rule(reactant: u8, agent: u8, product: u8, agent_product: u8, probability: f32) -> ReactionRule {
+        ReactionRule { reactant, agent, product, agent_product, probability, heat: 0 }
+    }
+
+    #[test]
+    fn add_rejects_bad_rules() {
+        let mut set = RuleSet::default();
+        assert!(set.add(rule(SPECIES_COUNT as u8, SPECIES_WATER, SPECIES_EMPTY, SPECIES_WATER, 1.0)).is_err());
+        assert!(set.add(rule(SPECIES_SAND, SPECIES_WATER, SPECIES_EMPTY, SPECIES_WATER, 1.5)).is_err());
+        assert!(set.rules().is_empty());
+        assert!(set.add(rule(SPECIES_SAND, SPECIES_WATER, SPECIES_EMPTY, SPECIES_WATER, 0.5)).is_ok());
+        assert_eq!(set.rules().len(), 1);
+    }
+
+    #[test]
+    fn certain_rule_turns_both_cells() {
+        let mut set = RuleSet::default();
+        set.add(rule(SPECIES_SAND, SPECIES_ACID, SPECIES_STONE, SPECIES_EMPTY, 1.0)).unwrap();
+        let mut cells = vec![0u8; 3 * 3 * crate::CELL_STRIDE];
+        set_cell_raw(&mut cells, 3, 1, 1, SPECIES_SAND, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 1, 0, SPECIES_ACID, 0, 20, 0);
+        set_cell_raw(&mut cells, 3, 0, 0, SPECIES_WALL, 0, 20, 0);
+        let mut chunks = ChunkMap::new(3, 3);
+        let mut events = EventSink::new(true);
+        set.apply(&mut cells, 3, 3, &mut chunks, &mut events);
+        assert_eq!(cells[cell_idx(3, 1, 1)], SPECIES_STONE);
+        assert_eq!(cells[cell_idx(3, 1, 0)], SPECIES_EMPTY);
+        assert_eq!(cells[cell_idx(3, 0, 0)], SPECIES_WALL, "Only the touching agent is used up");
+        assert_eq!(*events, [Event::Reacted { x: 1, y: 1, species: SPECIES_SAND, with: SPECIES_ACID }]);
+    }
+
+    #[test]
+    fn catalysts_stay_put() {
+        let mut set = RuleSet::default();
+        set.add(rule(SPECIES_WATER, SPECIES_WALL, SPECIES_STONE, SPECIES_WALL, 1.0)).unwrap();
+        let mut cells = vec![0u8; 2 * crate::CELL_STRIDE];
+        set_cell_raw(&mut cells, 2, 0, 0, SPECIES_WATER, 0, 20, 0);
+        set_cell_raw(&mut cells, 2, 1, 0, SPECIES_WALL, 7, 20, 0);
+        set.apply(&mut cells, 2, 1, &mut ChunkMap::new(2, 1), &mut EventSink::default());
+        assert_eq!(cells[cell_idx(2, 0, 0)], SPECIES_STONE);
+        assert_eq!(cells[cell_idx(2, 1, 0) + 1], 7, "The catalyst cell isn't rewritten");
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{
+    World, SPECIES_ACID, SPECIES_EMPTY, SPECIES_FIRE, SPECIES_ICE, SPECIES_LAVA, SPECIES_OIL, SPECIES_SAND,
+    SPECIES_STEAM, SPECIES_STONE, SPECIES_WALL, SPECIES_WATER, SPECIES_WOOD,
+};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+// ── Levels ────────────────────────────────────────────────────────────
+
+/// Condition a tutorial level is waiting for, checked against the live world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// At least `count` cells of `species` exist.
+    AtLeast { species: u8, count: u32 },
+    /// At most `count` cells of `species` remain.
+    AtMost { species: u8, count: u32 },
+}
+
+impl Goal {
+    /// (current, target) progress toward the goal, where `current >= target`
+    /// means the goal is met. `baseline` is the goal species' count when the
+    /// level was loaded.
+    fn measure(&self, world: &World, baseline: u32) -> (u32, u32) {
+        match *self {
+            Goal::AtLeast { species, count } => (count_cells(world, species).min(count), count),
+            Goal::AtMost { species, count } => {
+                let initial = baseline.max(count + 1);
+                let removed = initial.saturating_sub(count_cells(world, species).max(count));
+                (removed, initial - count)
+            }
+        }
+    }
+
+    fn species(&self) -> u8 {
+        match *self {
+            Goal::AtLeast { species, .. } | Goal::AtMost { species, .. } => species,
+        }
+    }
+}
+
+/// One step of the guided introduction. Hint IDs are keys into the
+/// frontend's own string table so the crate stays language-neutral.
+pub struct Level {
+    pub id: &'static str,
+    pub hint_ids: &'static [&'static str],
+    /// Species the player may paint (erasing is always allowed).
+    pub allowed_species: &'static [u8],
+    pub goal: Goal,
+    build: fn(&mut World),
+}
+
+fn count_cells(world: &World, species: u8) -> u32 {
+    world.count_species(species) as u32
+}
+
+fn fill_rect(world: &mut World, x0: usize, y0: usize, x1: usize, y1: usize, species: u8) {
+    for y in y0..y1.min(world.height()) {
+        for x in x0..x1.min(world.width()) {
+            world.set_cell(x, y, species);
+        }
+    }
+}
+
+/// Walls along the floor and both sides.
+fn build_basin(world: &mut World) {
+    let (w, h) = (world.width(), world.height());
+    fill_rect(world, 0, h.saturating_sub(1), w, h, SPECIES_WALL);
+    fill_rect(world, 0, 0, 1, h, SPECIES_WALL);
+    fill_rect(world, w.saturating_sub(1), 0, w, h, SPECIES_WALL);
+}
+
+fn build_sand_basics(world: &mut World) {
+    build_basin(world);
+}
+
+fn build_water_flows(world: &mut World) {
+    build_basin(world);
+    let (w, h) = (world.width(), world.height());
+    // A shelf halfway down that water has to spill off
+    fill_rect(world, 1, h / 2, w * 2 / 3, h / 2 + 1, SPECIES_WALL);
+}
+
+fn build_boil_water(world: &mut World) {
+    build_basin(world);
+    let (w, h) = (world.width(), world.height());
+    fill_rect(world, 1, h * 2 / 3, w.saturating_sub(1), h.saturating_sub(1), SPECIES_WATER);
+}
+
+fn build_burn_wood(world: &mut World) {
+    build_basin(world);
+    let (w, h) = (world.width(), world.height());
+    fill_rect(world, w / 3, h / 2, w * 2 / 3, h.saturating_sub(1), SPECIES_WOOD);
+}
+
+fn build_melt_ice(world: &mut World) {
+    build_basin(world);
+    let (w, h) = (world.width(), world.height());
+    fill_rect(world, w / 3, h / 2, w * 2 / 3, h.saturating_sub(1), SPECIES_ICE);
+}
+
+fn build_acid_dissolve(world: &mut World) {
+    build_basin(world);
+    let (w, h) = (world.width(), world.height());
+    fill_rect(world, (w / 2).saturating_sub(2), h / 3, w / 2 + 2, h.saturating_sub(1), SPECIES_STONE);
+}
+
+/// The guided introduction, in order. Each level unlocks the next.
+pub static LEVELS: &[Level] = &[
+    Level {
+        id: "sand-basics",
+        hint_ids: &["tutorial.sand.paint", "tutorial.sand.piles"],
+        allowed_species: &[SPECIES_SAND],
+        goal: Goal::AtLeast { species: SPECIES_SAND, count: 200 },
+        build: build_sand_basics,
+    },
+    Level {
+        id: "water-flows",
+        hint_ids: &["tutorial.water.paint", "tutorial.water.spreads"],
+        allowed_species: &[SPECIES_WATER, SPECIES_SAND],
+        goal: Goal::AtLeast { species: SPECIES_WATER, count: 300 },
+        build: build_water_flows,
+    },
+    Level {
+        id: "boil-water",
+        hint_ids: &["tutorial.boil.heat", "tutorial.boil.steam"],
+        allowed_species: &[SPECIES_LAVA],
+        goal: Goal::AtLeast { species: SPECIES_STEAM, count: 50 },
+        build: build_boil_water,
+    },
+    Level {
+        id: "burn-wood",
+        hint_ids: &["tutorial.fire.ignite", "tutorial.fire.fuel"],
+        allowed_species: &[SPECIES_FIRE, SPECIES_OIL],
+        goal: Goal::AtMost { species: SPECIES_WOOD, count: 0 },
+        build: build_burn_wood,
+    },
+    Level {
+        id: "melt-ice",
+        hint_ids: &["tutorial.ice.melt"],
+        allowed_species: &[SPECIES_LAVA, SPECIES_FIRE],
+        goal: Goal::AtMost { species: SPECIES_ICE, count: 0 },
+        build: build_melt_ice,
+    },
+    Level {
+        id: "acid-dissolve",
+        hint_ids: &["tutorial.acid.pour", "tutorial.acid.consumed"],
+        allowed_species: &[SPECIES_ACID],
+        goal: Goal::AtMost { species: SPECIES_STONE, count: 10 },
+        build: build_acid_dissolve,
+    },
+];
+
+// ── Progression ───────────────────────────────────────────────────────
+
+/// Tracks which tutorial level is loaded and which have been completed.
+/// The completion set round-trips through `completed_mask` so frontends can
+/// persist it (e.g. in localStorage).
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct Tutorial {
+    current: Option<usize>,
+    completed: u64,
+    baseline: u32,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl Tutorial {
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen(constructor))]
+    pub fn new() -> Tutorial {
+        Tutorial { current: None, completed: 0, baseline: 0 }
+    }
+
+    pub fn level_count(&self) -> usize { LEVELS.len() }
+
+    pub fn level_id(&self, level: usize) -> String {
+        LEVELS.get(level).map_or(String::new(), |l| l.id.to_string())
+    }
+
+    pub fn hint_count(&self, level: usize) -> usize {
+        LEVELS.get(level).map_or(0, |l| l.hint_ids.len())
+    }
+
+    pub fn hint_id(&self, level: usize, hint: usize) -> String {
+        LEVELS.get(level).and_then(|l| l.hint_ids.get(hint)).map_or(String::new(), |h| h.to_string())
+    }
+
+    pub fn allowed_species(&self, level: usize) -> Vec<u8> {
+        LEVELS.get(level).map_or(Vec::new(), |l| l.allowed_species.to_vec())
+    }
+
+    /// Whether the loaded level lets the player paint `species`. With no
+    /// level loaded everything is allowed.
+    pub fn is_species_allowed(&self, species: u8) -> bool {
+        match self.current {
+            Some(level) => species == SPECIES_EMPTY || LEVELS[level].allowed_species.contains(&species),
+            None => true,
+        }
+    }
+
+    /// A level is unlocked once every level before it is complete.
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level < LEVELS.len() && (0..level).all(|l| self.is_completed(l))
+    }
+
+    /// Clears `world` and builds the level's preset scene. Returns false for
+    /// unknown or still-locked levels.
+    pub fn load_level(&mut self, world: &mut World, level: usize) -> bool {
+        if !self.is_unlocked(level) { return false; }
+        world.clear();
+        (LEVELS[level].build)(world);
+        self.baseline = count_cells(world, LEVELS[level].goal.species());
+        self.current = Some(level);
+        true
+    }
+
+    /// Index of the loaded level, or -1 if none.
+    pub fn current_level(&self) -> i32 {
+        self.current.map_or(-1, |l| l as i32)
+    }
+
+    /// Fraction (0.0..=1.0) of the loaded level's goal reached in `world`.
+    pub fn progress(&self, world: &World) -> f32 {
+        let Some(level) = self.current else { return 0.0 };
+        let (current, target) = LEVELS[level].goal.measure(world, self.baseline);
+        if target == 0 { 1.0 } else { (current as f32 / target as f32).min(1.0) }
+    }
+
+    /// Checks the loaded level's goal against `world`, marking the level
+    /// complete when it is met. Returns true once the level is complete.
+    pub fn check(&mut self, world: &World) -> bool {
+        let Some(level) = self.current else { return false };
+        if self.progress(world) >= 1.0 {
+            self.mark_complete(level);
+        }
+        self.is_completed(level)
+    }
+
+    pub fn mark_complete(&mut self, level: usize) {
+        if level < LEVELS.len() { self.completed |= 1 << level; }
+    }
+
+    pub fn is_completed(&self, level: usize) -> bool {
+        level < LEVELS.len() && self.completed & (1 << level) != 0
+    }
+
+    /// Bit `n` set = level `n` complete.
+    pub fn completed_mask(&self) -> u64 { self.completed }
+
+    pub fn set_completed_mask(&mut self, mask: u64) {
+        self.completed = mask & ((1u64 << LEVELS.len()) - 1);
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self { Tutorial::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_have_unique_ids_and_fit_mask() {
+        assert!(LEVELS.len() <= 64);
+        for (i, a) in LEVELS.iter().enumerate() {
+            assert!(!a.hint_ids.is_empty(), "{} has no hints", a.id);
+            assert!(!a.allowed_species.is_empty(), "{} allows nothing", a.id);
+            for b in &LEVELS[i + 1..] {
+                assert_ne!(a.id, b.id);
+            }
+        }
+    }
+
+    #[test]
+    fn levels_build_in_tiny_worlds() {
+        for (width, height) in [(0, 0), (1, 1), (3, 2), (4, 40)] {
+            let mut w = World::new(width, height);
+            for level in LEVELS { (level.build)(&mut w); }
+        }
+    }
+
+    #[test]
+    fn levels_unlock_in_order() {
+        let mut t = Tutorial::new();
+        let mut w = World::new(40, 30);
+        assert!(t.is_unlocked(0));
+        assert!(!t.load_level(&mut w, 1), "Second level starts locked");
+        t.mark_complete(0);
+        assert!(t.load_level(&mut w, 1));
+        assert_eq!(t.current_level(), 1);
+        assert!(!t.load_level(&mut w, LEVELS.len()));
+    }
+
+    #[test]
+    fn allowed_species_follow_loaded_level() {
+        let mut t = Tutorial::new();
+        let mut w = World::new(40, 30);
+        assert!(t.is_species_allowed(SPECIES_LAVA));
+        t.load_level(&mut w, 0);
+        assert!(t.is_species_allowed(SPECIES_SAND));
+        assert!(t.is_species_allowed(SPECIES_EMPTY));
+        assert!(!t.is_species_allowed(SPECIES_LAVA));
+    }
+
+    #[test]
+    fn sand_level_completes_when_goal_met() {
+        let mut t = Tutorial::new();
+        let mut w = World::new(40, 30);
+        t.load_level(&mut w, 0);
+        assert!(!t.check(&w));
+        assert_eq!(t.progress(&w), 0.0);
+        fill_rect(&mut w, 1, 10, 39, 16, SPECIES_SAND);
+        assert!(t.check(&w));
+        assert!(t.is_completed(0));
+        assert!(t.is_unlocked(1));
+    }
+
+    #[test]
+    fn removal_goal_tracks_fraction_removed() {
+        let mut t = Tutorial::new();
+        t.set_completed_mask(0b111);
+        let mut w = World::new(40, 30);
+        assert!(t.load_level(&mut w, 3));
+        let wood = count_cells(&w, SPECIES_WOOD);
+        assert!(wood > 0);
+        assert_eq!(t.progress(&w), 0.0);
+        let mut removed = 0;
+        'outer: for y in 0..30 {
+            for x in 0..40 {
+                if removed * 2 >= wood { break 'outer; }
+                if crate::get_species(&w.cells, w.width, x, y) == SPECIES_WOOD {
+                    w.set_cell(x, y, SPECIES_EMPTY);
+                    removed += 1;
+                }
+            }
+        }
+        let p = t.progress(&w);
+        assert!((0.45..=0.55).contains(&p), "progress {}", p);
+        fill_rect(&mut w, 0, 0, 40, 29, SPECIES_EMPTY);
+        assert!(t.check(&w));
+    }
+
+    #[test]
+    fn completed_mask_round_trips_and_drops_unknown_bits() {
+        let mut t = Tutorial::new();
+        t.set_completed_mask(u64::MAX);
+        assert_eq!(t.completed_mask(), (1 << LEVELS.len()) - 1);
+        let mut u = Tutorial::new();
+        u.set_completed_mask(0b101);
+        assert!(u.is_completed(0) && !u.is_completed(1) && u.is_completed(2));
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{species_id, species_name, CELL_STRIDE, SPECIES_EMPTY};
+
+/// Largest world `Scene::validate` accepts, as for RLE scenes.
+const MAX_CELLS: usize = 1 << 24;
+
+/// Human-editable scene file for fixtures and hand-built levels: the grid
+/// size and a sparse list of its non-empty cells, species by name.
+///
+/// ```json
+/// {"width": 4, "height": 3, "cells": [{"x": 1, "y": 2, "species": "sand", "temp": 12}]}
+/// ```
+///
+/// `temp` may be left out, for the temperature the species is painted at.
+/// Like RLE scenes, `ra` and the clock aren't stored.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct Scene {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) cells: Vec<SceneCell>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct SceneCell {
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+    pub(crate) species: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) temp: Option<u8>,
+}
+
+impl Scene {
+    /// The non-empty cells of a `width` x `height` grid.
+    pub(crate) fn from_cells(cells: &[u8], width: usize, height: usize) -> Scene {
+        let cells = cells
+            .chunks_exact(CELL_STRIDE)
+            .enumerate()
+            .filter(|(_, c)| c[0] != SPECIES_EMPTY)
+            .map(|(n, c)| SceneCell { x: n % width, y: n / width, species: species_name(c[0]), temp: Some(c[2]) })
+            .collect();
+        Scene { width, height, cells }
+    }
+
+    /// Checks the size and every cell, returning each cell's species ID in
+    /// order.
+    pub(crate) fn validate(&self) -> Result<Vec<u8>, String> {
+        self.width.checked_mul(self.height).filter(|&n| n <= MAX_CELLS).ok_or("world too large")?;
+        self.cells
+            .iter()
+            .map(|c| {
+                if c.x >= self.width || c.y >= self.height {
+                    return Err(format!("cell ({}, {}) is off the grid", c.x, c.y));
+                }
+                species_id(&c.species).ok_or_else(|| format!("unknown species \"{}\"", c.species))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_bad_cells() {
+        let cell = |x, species: &str| SceneCell { x, y: 0, species: species.into(), temp: None };
+        let scene = |cells| Scene { width: 2, height: 1, cells };
+        assert_eq!(scene(vec![cell(1, "wall")]).validate(), Ok(vec![4]));
+        assert!(scene(vec![cell(2, "wall")]).validate().is_err(), "Off the grid");
+        assert!(scene(vec![cell(0, "brick")]).validate().is_err(), "Unknown species");
+        assert!(Scene { width: 1 << 13, height: 1 << 13, cells: Vec::new() }.validate().is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+use crate::custom::{Behavior, CellWrite, Neighborhood};
+use crate::rand;
+
+/// Work one script call may do before it's cut off, so a runaway loop
+/// fails that cell's update instead of hanging the tick. Counted in Rhai
+/// operations, roughly one per expression evaluated.
+const MAX_OPERATIONS: u64 = 50_000;
+/// Deepest a script's function calls may nest.
+const MAX_CALL_LEVELS: usize = 16;
+
+/// Compiles a Rhai script into a custom species behavior. See
+/// `World::register_script` for what the script sees and returns.
+pub(crate) fn compile(source: &str) -> Result<Behavior, String> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS).set_max_call_levels(MAX_CALL_LEVELS);
+    engine.register_fn("rand", rand);
+    let ast = engine.compile(source).map_err(|e| e.to_string())?;
+    if !ast.iter_functions().any(|f| f.name == "update" && f.params.len() == 3) {
+        return Err("script must define fn update(species, temps, ra)".into());
+    }
+    Ok(Arc::new(move |n: &Neighborhood| run(&engine, &ast, n)))
+}
+
+fn run(engine: &Engine, ast: &AST, n: &Neighborhood) -> Vec<CellWrite> {
+    let mut species = Array::with_capacity(9);
+    let mut temps = Array::with_capacity(9);
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let c = n.get(dx, dy);
+            species.push(Dynamic::from_int(c.species as rhai::INT));
+            temps.push(Dynamic::from_int(c.temperature as rhai::INT));
+        }
+    }
+    let ra = n.center().ra as rhai::INT;
+    let Ok(result) = engine.call_fn::<Array>(&mut Scope::new(), ast, "update", (species, temps, ra)) else {
+        return Vec::new();
+    };
+    result.into_iter().filter_map(|w| w.try_cast::<Map>()).flat_map(|m| writes_of(&m)).collect()
+}
+
+/// The writes one returned map asks for, in a fixed order: the swap last,
+/// so the other fields apply to whatever is there before the move.
+fn writes_of(m: &Map) -> Vec<CellWrite> {
+    let int = |key: &str| m.get(key).and_then(|v| v.as_int().ok());
+    let byte = |key: &str| int(key).and_then(|v| u8::try_from(v).ok());
+    let (Some(dx), Some(dy)) = (int("dx"), int("dy")) else { return Vec::new(); };
+    let (Ok(dx), Ok(dy)) = (i8::try_from(dx), i8::try_from(dy)) else { return Vec::new(); };
+
+    let mut out = Vec::new();
+    if let Some(species) = byte("species") { out.push(CellWrite::Species { dx, dy, species }); }
+    if let Some(temp) = byte("temp") { out.push(CellWrite::Temperature { dx, dy, temp }); }
+    if let Some(ra) = byte("ra") { out.push(CellWrite::Ra { dx, dy, ra }); }
+    if m.get("swap").and_then(|v| v.as_bool().ok()) == Some(true) { out.push(CellWrite::Swap { dx, dy }); }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SPECIES_SAND;
+
+    fn empty_neighborhood() -> Neighborhood {
+        let mut cells = vec![0u8; 3 * 3 * crate::CELL_STRIDE];
+        crate::set_cell_raw(&mut cells, 3, 1, 1, SPECIES_SAND, 5, 30, 0);
+        Neighborhood::gather(&cells, 3, 3, 1, 1)
+    }
+
+    #[test]
+    fn compile_requires_an_update_function() {
+        assert!(compile("fn step(a) { [] }").is_err());
+        assert!(compile("fn update(a, b, c) { [").is_err(), "Syntax errors are reported");
+        assert!(compile("fn update(a, b, c) { [] }").is_ok());
+    }
+
+    #[test]
+    fn script_writes_become_cell_writes() {
+        let behavior = compile(
+            "fn update(species, temps, ra) {
+                if species[7] == 0 { [#{ dx: 0, dy: 1, swap: true, temp: temps[4] + ra }] } else { [] }
+            }",
+        ).unwrap();
+        let writes = behavior(&empty_neighborhood());
+        assert_eq!(writes, [CellWrite::Temperature { dx: 0, dy: 1, temp: 35 }, CellWrite::Swap { dx: 0, dy: 1 }]);
+    }
+
+    #[test]
+    fn bad_output_writes_nothing() {
+        let n = empty_neighborhood();
+        assert!(compile("fn update(a, b, c) { a[99] }").unwrap()(&n).is_empty(), "Runtime errors");
+        assert!(compile("fn update(a, b, c) { [#{ dx: 0, species: 1 }] }").unwrap()(&n).is_empty(), "No dy");
+        let out_of_range = compile("fn update(a, b, c) { [#{ dx: 0, dy: 0, species: 300 }] }").unwrap();
+        assert!(out_of_range(&n).is_empty());
+    }
+
+    #[test]
+    fn runaway_scripts_are_cut_off() {
+        let n = empty_neighborhood();
+        assert!(compile("fn update(a, b, c) { loop {} }").unwrap()(&n).is_empty());
+        let deep = compile("fn dig(n) { dig(n + 1) } fn update(a, b, c) { dig(0) }").unwrap();
+        assert!(deep(&n).is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::custom::CUSTOM_SPECIES_LAST;
+use crate::{CELL_STRIDE, SPECIES_EMPTY};
+
+/// Species IDs with a count: the built-in ones and the custom range.
+const COUNTED: usize = CUSTOM_SPECIES_LAST as usize + 1;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Summary of the grid as of the end of the last tick: how many cells of
+/// each species there are, temperature aggregates over the non-empty
+/// cells, and how busy the movement pass was. Tallied while the tick runs,
+/// so reading it is free.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldStats {
+    counts: [u32; COUNTED],
+    temp_sum: u64,
+    /// Coldest non-empty cell; 0 in an empty world.
+    pub min_temp: u8,
+    /// Hottest non-empty cell; 0 in an empty world.
+    pub max_temp: u8,
+    /// Cells the movement pass moved or changed.
+    pub moved: u32,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+impl WorldStats {
+    /// Number of `species` cells; 0 for unknown species.
+    pub fn count(&self, species: u8) -> u32 {
+        self.counts.get(species as usize).copied().unwrap_or(0)
+    }
+
+    /// Cell counts indexed by species ID.
+    pub fn counts(&self) -> Vec<u32> {
+        self.counts.to_vec()
+    }
+
+    /// Cells holding anything at all.
+    pub fn occupied(&self) -> u32 {
+        self.counts.iter().sum::<u32>() - self.counts[SPECIES_EMPTY as usize]
+    }
+
+    /// Mean temperature of the non-empty cells; 0 in an empty world.
+    pub fn mean_temp(&self) -> f32 {
+        match self.occupied() {
+            0 => 0.0,
+            n => (self.temp_sum as f64 / n as f64) as f32,
+        }
+    }
+}
+
+impl WorldStats {
+    /// Starts a sweep over the grid.
+    pub(crate) fn tally(moved: u32) -> Tally {
+        Tally { counts: [0; 256], temps: [0; 256], moved }
+    }
+
+    /// Tallies a whole cell buffer in one go.
+    pub(crate) fn of(cells: &[u8]) -> WorldStats {
+        let mut tally = WorldStats::tally(0);
+        for c in cells.chunks_exact(CELL_STRIDE) { tally.record(c[0], c[2]); }
+        tally.finish()
+    }
+}
+
+/// Counts gathered cell by cell, branch-free so sweeping a large grid
+/// stays cheap: cells per species and non-empty cells per temperature.
+pub(crate) struct Tally {
+    counts: [u32; 256],
+    temps: [u32; 256],
+    moved: u32,
+}
+
+impl Tally {
+    #[inline(always)]
+    pub(crate) fn record(&mut self, species: u8, temp: u8) {
+        self.counts[species as usize] += 1;
+        self.temps[temp as usize] += (species != SPECIES_EMPTY) as u32;
+    }
+
+    /// Call once every cell is recorded. Unknown species aren't counted.
+    pub(crate) fn finish(self) -> WorldStats {
+        let mut counts = [0; COUNTED];
+        counts.copy_from_slice(&self.counts[..COUNTED]);
+        let present = |t: &usize| self.temps[*t] > 0;
+        WorldStats {
+            counts,
+            temp_sum: self.temps.iter().enumerate().map(|(t, &n)| t as u64 * n as u64).sum(),
+            min_temp: (0..256).find(present).unwrap_or(0) as u8,
+            max_temp: (0..256).rev().find(present).unwrap_or(0) as u8,
+            moved: self.moved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SPECIES_LAVA, SPECIES_WATER};
+
+    #[test]
+    fn aggregates_skip_empty_cells() {
+        let mut tally = WorldStats::tally(3);
+        for (species, temp) in [(SPECIES_EMPTY, 0), (SPECIES_WATER, 20), (SPECIES_WATER, 30), (SPECIES_LAVA, 220)] {
+            tally.record(species, temp);
+        }
+        let stats = tally.finish();
+        assert_eq!((stats.count(SPECIES_WATER), stats.count(SPECIES_EMPTY), stats.occupied()), (2, 1, 3));
+        assert_eq!((stats.min_temp, stats.max_temp, stats.moved), (20, 220, 3));
+        assert!((stats.mean_temp() - 90.0).abs() < 1e-4);
+        assert_eq!(stats.count(200), 0);
+
+        let empty = WorldStats::of(&[0; 4 * CELL_STRIDE]);
+        assert_eq!((empty.count(SPECIES_EMPTY), empty.min_temp, empty.mean_temp()), (4, 0, 0.0));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    CELL_STRIDE, SPECIES_ACID, SPECIES_LAVA, SPECIES_MERCURY, SPECIES_MOLTEN_WAX, SPECIES_NITRO, SPECIES_OIL,
+    SPECIES_SALTWATER, SPECIES_WATER,
+};
+
+/// Species that form liquid bodies for surface extraction.
+#[inline(always)]
+pub(crate) fn is_liquid(species: u8) -> bool {
+    matches!(species, SPECIES_WATER | SPECIES_SALTWATER | SPECIES_OIL | SPECIES_LAVA | SPECIES_ACID | SPECIES_MERCURY | SPECIES_MOLTEN_WAX | SPECIES_NITRO)
+}
+
+/// Closed outline of one contiguous body of a single liquid species.
+/// Points are in cell units (cell (x, y) spans x..x+1, y..y+1) and run
+/// with the liquid on their right in screen (y-down) space, so outer
+/// boundaries wind clockwise and holes counter-clockwise — nonzero and
+/// even-odd fills both draw the body correctly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidContour {
+    pub species: u8,
+    pub points: Vec<(f32, f32)>,
+}
+
+/// Doubled coordinates of an edge midpoint on the padded sample grid.
+type Point = (i32, i32);
+
+/// Traces every liquid body in `cells` with marching squares over cell
+/// centers. The grid is padded with a ring of dry samples so bodies
+/// touching the world edge still close.
+pub(crate) fn extract(cells: &[u8], width: usize, height: usize) -> Vec<LiquidContour> {
+    let mut present = [false; 256];
+    for c in cells.chunks_exact(CELL_STRIDE) {
+        if is_liquid(c[0]) { present[c[0] as usize] = true; }
+    }
+
+    let mut contours = Vec::new();
+    for species in (0..=255u8).filter(|&s| present[s as usize]) {
+        let inside = |px: i32, py: i32| -> bool {
+            // Padded sample (px, py) is cell (px - 1, py - 1)
+            if px < 1 || py < 1 || px as usize > width || py as usize > height { return false; }
+            cells[((py as usize - 1) * width + px as usize - 1) * CELL_STRIDE] == species
+        };
+
+        let mut next: BTreeMap<Point, Point> = BTreeMap::new();
+        for y in 0..=height as i32 {
+            for x in 0..=width as i32 {
+                let case = (inside(x, y) as u8) << 3
+                    | (inside(x + 1, y) as u8) << 2
+                    | (inside(x + 1, y + 1) as u8) << 1
+                    | inside(x, y + 1) as u8;
+                let t = (2 * x + 1, 2 * y);
+                let r = (2 * x + 2, 2 * y + 1);
+                let b = (2 * x + 1, 2 * y + 2);
+                let l = (2 * x, 2 * y + 1);
+                let segments: &[(Point, Point)] = match case {
+                    1 => &[(l, b)],
+                    2 => &[(b, r)],
+                    3 => &[(l, r)],
+                    4 => &[(r, t)],
+                    5 => &[(l, b), (r, t)],
+                    6 => &[(b, t)],
+                    7 => &[(l, t)],
+                    8 => &[(t, l)],
+                    9 => &[(t, b)],
+                    10 => &[(t, l), (b, r)],
+                    11 => &[(t, r)],
+                    12 => &[(r, l)],
+                    13 => &[(r, b)],
+                    14 => &[(b, l)],
+                    _ => &[],
+                };
+                for &(from, to) in segments {
+                    next.insert(from, to);
+                }
+            }
+        }
+
+        while let Some((&start, _)) = next.iter().next() {
+            let mut loop_points = vec![start];
+            let mut at = start;
+            while let Some(to) = next.remove(&at) {
+                if to == start { break; }
+                loop_points.push(to);
+                at = to;
+            }
+            contours.push(LiquidContour { species, points: simplify(&loop_points) });
+        }
+    }
+    contours
+}
+
+/// Drops points that lie on a straight run between their neighbors and
+/// converts doubled padded coordinates to cell units.
+fn simplify(points: &[Point]) -> Vec<(f32, f32)> {
+    let n = points.len();
+    let mut out = Vec::with_capacity(n);
+    for i in 0..n {
+        let prev = points[(i + n - 1) % n];
+        let cur = points[i];
+        let next = points[(i + 1) % n];
+        let cross = (cur.0 - prev.0) * (next.1 - cur.1) - (cur.1 - prev.1) * (next.0 - cur.0);
+        if cross != 0 {
+            // Padded center p sits at cell p - 1 + 0.5
+            out.push((cur.0 as f32 / 2.0 - 0.5, cur.1 as f32 / 2.0 - 0.5));
+        }
+    }
+    out
+}
+
+/// Packs contours as `[species, point_count, x0, y0, x1, y1, ...]` repeated,
+/// for frontends that can't take structured data across the wasm boundary.
+pub(crate) fn flatten(contours: &[LiquidContour]) -> Vec<f32> {
+    let mut out = Vec::new();
+    for c in contours {
+        out.push(c.species as f32);
+        out.push(c.points.len() as f32);
+        for &(x, y) in &c.points {
+            out.push(x);
+            out.push(y);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_cell_raw, SPECIES_WALL};
+
+    fn grid(width: usize, height: usize, fill: &[(usize, usize, u8)]) -> Vec<u8> {
+        let mut cells = vec![0; width * height * CELL_STRIDE];
+        for &(x, y, s) in fill {
+            set_cell_raw(&mut cells, width, x, y, s, 0, 0, 0);
+        }
+        cells
+    }
+
+    /// Shoelace area; positive = clockwise in y-down space.
+    fn signed_area(points: &[(f32, f32)]) -> f32 {
+        let n = points.len();
+        (0..n).map(|i| {
+            let (x0, y0) = points[i];
+            let (x1, y1) = points[(i + 1) % n];
+            x0 * y1 - x1 * y0
+        }).sum::<f32>() / 2.0
+    }
+
+    #[test]
+    fn single_cell_makes_a_diamond() {
+        let cells = grid(3, 3, &[(1, 1, SPECIES_WATER)]);
+        let contours = extract(&cells, 3, 3);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].species, SPECIES_WATER);
+        assert_eq!(contours[0].points.len(), 4);
+        assert!(signed_area(&contours[0].points) > 0.0);
+    }
+
+    #[test]
+    fn separate_bodies_and_species_get_separate_contours() {
+        let cells = grid(7, 3, &[
+            (0, 1, SPECIES_WATER), (1, 1, SPECIES_WATER),
+            (3, 1, SPECIES_OIL),
+            (5, 1, SPECIES_WATER), (6, 1, SPECIES_WATER),
+            (4, 1, SPECIES_WALL),
+        ]);
+        let contours = extract(&cells, 7, 3);
+        assert_eq!(contours.iter().filter(|c| c.species == SPECIES_WATER).count(), 2);
+        assert_eq!(contours.iter().filter(|c| c.species == SPECIES_OIL).count(), 1);
+    }
+
+    #[test]
+    fn ring_has_outer_boundary_and_hole() {
+        let mut fill = vec![];
+        for y in 0..5 {
+            for x in 0..5 {
+                if x == 0 || y == 0 || x == 4 || y == 4 { fill.push((x, y, SPECIES_WATER)); }
+            }
+        }
+        let cells = grid(5, 5, &fill);
+        let contours = extract(&cells, 5, 5);
+        assert_eq!(contours.len(), 2);
+        let areas: Vec<f32> = contours.iter().map(|c| signed_area(&c.points)).collect();
+        assert!(areas.iter().any(|&a| a > 0.0) && areas.iter().any(|&a| a < 0.0), "areas {:?}", areas);
+    }
+
+    #[test]
+    fn straight_runs_are_simplified() {
+        let fill: Vec<_> = (0..3).flat_map(|y| (0..10).map(move |x| (x, y, SPECIES_WATER))).collect();
+        let cells = grid(10, 3, &fill);
+        let contours = extract(&cells, 10, 3);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].points.len(), 8, "A rectangle with cut corners has 8 vertices");
+    }
+
+    #[test]
+    fn flatten_layout() {
+        let contours = vec![LiquidContour { species: 2, points: vec![(1.0, 2.0), (3.0, 4.0)] }];
+        assert_eq!(flatten(&contours), vec![2.0, 2.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    set_cell_raw, World, TEMP_STONE_MELT, SPECIES_CLONE, SPECIES_COOLER, SPECIES_FIRE, SPECIES_HEATER, SPECIES_SAND,
+    SPECIES_STONE, SPECIES_VOID, SPECIES_WALL, SPECIES_WATER, SPECIES_WOOD,
+};
+
+/// Heater setting for the thermal gradient: hot, but short of melting stone.
+const GRADIENT_HOT: u8 = TEMP_STONE_MELT - 10;
+
+/// Standard load scenes for benchmarks and tests, each built at whatever
+/// size is asked for so scaling can be measured. Each stresses one hot path
+/// of the tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Testbed {
+    /// The bottom half packed with sand that has nowhere to go: the cost of
+    /// a world at rest.
+    SettledSand,
+    /// A clone spout pouring water off a ledge into drains along the floor:
+    /// steady falling and spreading that never settles.
+    Waterfall,
+    /// A stack of wood lit along its base: fire spread, smoke and embers.
+    BigFire,
+    /// A slab of stone between a row of heaters and a row of coolers: every
+    /// cell conducting heat, nothing moving.
+    ThermalGradient,
+}
+
+impl Testbed {
+    pub const ALL: [Testbed; 4] = [Testbed::SettledSand, Testbed::Waterfall, Testbed::BigFire, Testbed::ThermalGradient];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Testbed::SettledSand => "settled_sand",
+            Testbed::Waterfall => "waterfall",
+            Testbed::BigFire => "big_fire",
+            Testbed::ThermalGradient => "thermal_gradient",
+        }
+    }
+
+    /// A new `width` x `height` world holding the scene, not yet ticked.
+    /// Sizes below 16x16 are bumped up to that.
+    pub fn build(self, width: usize, height: usize) -> World {
+        let mut world = World::new(width.max(16), height.max(16));
+        let (w, h) = (world.width(), world.height());
+        match self {
+            Testbed::SettledSand => {
+                fill_rect(&mut world, 0, h - 1, w, h, SPECIES_WALL);
+                fill_rect(&mut world, 0, h / 2, w, h - 1, SPECIES_SAND);
+            }
+            Testbed::Waterfall => {
+                fill_rect(&mut world, 0, h - 1, w, h, SPECIES_VOID);
+                fill_rect(&mut world, 0, h / 4, w / 3, h / 4 + 1, SPECIES_WALL);
+                for x in 0..w / 6 {
+                    set_cell_raw(&mut world.cells, w, x, 0, SPECIES_CLONE, SPECIES_WATER, 0, 0);
+                }
+                world.chunks.wake_all();
+            }
+            Testbed::BigFire => {
+                fill_rect(&mut world, 0, h - 1, w, h, SPECIES_WALL);
+                fill_rect(&mut world, w / 8, h / 3, w - w / 8, h - 2, SPECIES_WOOD);
+                fill_rect(&mut world, w / 8, h - 2, w - w / 8, h - 1, SPECIES_FIRE);
+            }
+            Testbed::ThermalGradient => {
+                fill_rect(&mut world, 0, 1, w, h - 1, SPECIES_STONE);
+                fill_rect(&mut world, 0, 0, w, 1, SPECIES_COOLER);
+                fill_rect(&mut world, 0, h - 1, w, h, SPECIES_HEATER);
+                for x in 0..w { world.set_cell_temp(x, h - 1, GRADIENT_HOT); }
+            }
+        }
+        world
+    }
+}
+
+fn fill_rect(world: &mut World, x0: usize, y0: usize, x1: usize, y1: usize, species: u8) {
+    for y in y0..y1 {
+        for x in x0..x1 {
+            world.set_cell(x, y, species);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn testbeds_stay_busy_or_idle_as_advertised() {
+        for size in [(16, 16), (64, 48)] {
+            for bed in Testbed::ALL {
+                let mut world = bed.build(size.0, size.1);
+                for _ in 0..30 { world.tick(); }
+                let moved = world.stats().moved;
+                match bed {
+                    Testbed::SettledSand | Testbed::ThermalGradient => assert_eq!(moved, 0, "{} at {size:?}", bed.name()),
+                    Testbed::Waterfall | Testbed::BigFire => assert!(moved > 0, "{} at {size:?}", bed.name()),
+                }
+            }
+        }
+        let mut hot = Testbed::ThermalGradient.build(20, 20);
+        for _ in 0..50 { hot.tick(); }
+        assert!(hot.temperature_at(10, 18) > hot.temperature_at(10, 1));
+    }
+}
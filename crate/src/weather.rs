@@ -0,0 +1,237 @@
+use std::f32::consts::TAU;
+
+use crate::caps::Caps;
+use crate::chunks::ChunkMap;
+use crate::{
+    cell_idx, get_species, is_pinned, rand, spawn, SPECIES_DIRT, SPECIES_EMPTY, SPECIES_MUD, SPECIES_SNOW,
+    SPECIES_STEAM, SPECIES_WATER, TEMP_AMBIENT,
+};
+
+/// Chance per tick that an open cell on the top row gets a raindrop or
+/// snowflake at full intensity.
+const MAX_PRECIPITATION_CHANCE: f64 = 0.02;
+
+/// Ticks between evaporation sweeps, which cover the whole grid since
+/// puddles at rest sit in sleeping chunks.
+pub(crate) const EVAPORATION_INTERVAL: u64 = 16;
+/// Chance per sweep that exposed water at ambient temperature in bone-dry
+/// air evaporates. Warmer water goes faster, up to `MAX_EVAPORATION_BOOST`
+/// times as fast.
+const EVAPORATION_CHANCE: f64 = 0.004;
+const MAX_EVAPORATION_BOOST: f64 = 4.0;
+/// Humidity of a new world.
+pub(crate) const DEFAULT_HUMIDITY: f32 = 0.5;
+
+/// Day cycle started by `set_time_of_day` when none is running: a few
+/// minutes at 60 ticks a second, cold enough at night to freeze water.
+pub(crate) const DEFAULT_DAY_PERIOD: u32 = 12_000;
+pub(crate) const DEFAULT_DAY_SWING: u8 = 8;
+/// Time of day, as a fraction of the day since midnight, at which a day
+/// cycle starts.
+const DAY_START: f32 = 0.25;
+
+/// What falls from the sky. Intensity runs from 0.0 (nothing) to 1.0.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Weather {
+    Clear,
+    Rain { intensity: f32 },
+    Snow { intensity: f32 },
+}
+
+/// Weather and the day/night cycle. Both are off until set, and the
+/// world drops this whole struct once they are.
+pub(crate) struct Sky {
+    pub(crate) weather: Weather,
+    // Ticks per full day and how far the ambient temperature swings
+    // above and below the world's base ambient over one.
+    day: Option<(u32, u8)>,
+    tick: u32,
+}
+
+impl Sky {
+    pub(crate) fn new() -> Sky {
+        Sky { weather: Weather::Clear, day: None, tick: 0 }
+    }
+
+    pub(crate) fn set_day_cycle(&mut self, period: u32, swing: u8) {
+        self.day = (period > 0 && swing > 0).then_some((period, swing));
+        self.tick = 0;
+    }
+
+    pub(crate) fn clear_day_cycle(&mut self) {
+        self.day = None;
+    }
+
+    /// True once there's neither weather nor a day cycle left to run.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.weather == Weather::Clear && self.day.is_none()
+    }
+
+    /// How far through the day it is, 0.0 at midnight through 0.5 at noon;
+    /// always noon without a day cycle.
+    pub(crate) fn time_of_day(&self) -> f32 {
+        let Some((period, _)) = self.day else { return 0.5; };
+        ((self.tick as f32 / period as f32) + DAY_START).fract()
+    }
+
+    /// Jumps the clock to `time` (fraction of the day since midnight,
+    /// wrapping), starting the default day cycle if none is running.
+    pub(crate) fn set_time_of_day(&mut self, time: f32) {
+        let (period, _) = *self.day.get_or_insert((DEFAULT_DAY_PERIOD, DEFAULT_DAY_SWING));
+        let phase = (time - DAY_START).rem_euclid(1.0);
+        self.tick = ((phase * period as f32).round() as u32) % period;
+    }
+
+    /// Daylight, 0.0 (night) to 1.0 (full sun). Dawn and dusk are half
+    /// lit, and it's full dark for the middle third of the night.
+    pub(crate) fn light(&self) -> f32 {
+        let Some((period, _)) = self.day else { return 1.0; };
+        let phase = (self.tick % period) as f32 / period as f32;
+        (0.5 + (phase * TAU).sin()).clamp(0.0, 1.0)
+    }
+
+    /// The temperature everything drifts toward right now. Starts each day
+    /// at `base` in the morning, warmest at noon, coldest at midnight.
+    pub(crate) fn ambient(&self, base: u8) -> u8 {
+        let Some((period, swing)) = self.day else { return base; };
+        let phase = (self.tick % period) as f32 / period as f32;
+        let t = base as f32 + swing as f32 * (phase * TAU).sin();
+        t.round().clamp(0.0, 255.0) as u8
+    }
+
+    /// One tick: advances the clock and drops fresh precipitation along
+    /// the top row. New particles count against population caps.
+    pub(crate) fn step(
+        &mut self, cells: &mut [u8], width: usize, clock: u8,
+        mut caps: Option<&mut Caps>, chunks: &mut ChunkMap,
+    ) {
+        if let Some((period, _)) = self.day {
+            self.tick = (self.tick + 1) % period;
+        }
+        let (species, intensity) = match self.weather {
+            Weather::Clear => return,
+            Weather::Rain { intensity } => (SPECIES_WATER, intensity),
+            Weather::Snow { intensity } => (SPECIES_SNOW, intensity),
+        };
+        let chance = intensity.clamp(0.0, 1.0) as f64 * MAX_PRECIPITATION_CHANCE;
+        for x in 0..width {
+            if get_species(cells, width, x, 0) != SPECIES_EMPTY || rand() >= chance { continue; }
+            if caps.as_deref_mut().is_some_and(|caps| !caps.admit(species, SPECIES_EMPTY)) { continue; }
+            spawn(cells, width, x, 0, species, clock);
+            chunks.wake_area(x, 0, 1);
+        }
+    }
+}
+
+/// Slow evaporation below boiling: now and then water with open air
+/// above it turns to steam, less often the more humid the air and more
+/// often the warmer the water. The steam rises off as if boiled. Exposed
+/// mud dries back to dirt the same way, steaming into the air above it.
+pub(crate) fn evaporate(
+    cells: &mut [u8], width: usize, height: usize, humidity: f32, clock: u8, chunks: &mut ChunkMap,
+) {
+    let dryness = 1.0 - humidity.clamp(0.0, 1.0) as f64;
+    if dryness <= 0.0 { return; }
+    for y in 1..height {
+        for x in 0..width {
+            let i = cell_idx(width, x, y);
+            let species = cells[i];
+            if !matches!(species, SPECIES_WATER | SPECIES_MUD) || is_pinned(cells, i) { continue; }
+            if get_species(cells, width, x, y - 1) != SPECIES_EMPTY { continue; }
+            let warmth = (cells[i + 2] as f64 / TEMP_AMBIENT as f64).clamp(1.0 / MAX_EVAPORATION_BOOST, MAX_EVAPORATION_BOOST);
+            if rand() >= EVAPORATION_CHANCE * dryness * warmth { continue; }
+            if species == SPECIES_MUD {
+                cells[i] = SPECIES_DIRT;
+                spawn(cells, width, x, y - 1, SPECIES_STEAM, clock);
+            } else {
+                spawn(cells, width, x, y, SPECIES_STEAM, clock);
+            }
+            chunks.wake_area(x, y, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CELL_STRIDE;
+
+    #[test]
+    fn ambient_follows_the_day() {
+        let mut sky = Sky::new();
+        assert_eq!(sky.ambient(TEMP_AMBIENT), TEMP_AMBIENT);
+        sky.set_day_cycle(100, 6);
+        let mut cells = vec![0u8; 4 * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(4, 1);
+        let mut temps = Vec::new();
+        for _ in 0..100 {
+            temps.push(sky.ambient(TEMP_AMBIENT));
+            sky.step(&mut cells, 4, 0, None, &mut chunks);
+        }
+        assert_eq!(temps[0], TEMP_AMBIENT);
+        assert_eq!(temps[25], TEMP_AMBIENT + 6, "Noon is warmest");
+        assert_eq!(temps[75], TEMP_AMBIENT - 6, "Midnight is coldest");
+        assert_eq!(sky.ambient(TEMP_AMBIENT), TEMP_AMBIENT, "A new day starts where the last began");
+        sky.clear_day_cycle();
+        assert!(sky.is_idle());
+    }
+
+    #[test]
+    fn time_of_day_sets_the_clock_and_the_light() {
+        let mut sky = Sky::new();
+        assert_eq!(sky.light(), 1.0);
+        sky.set_time_of_day(0.0);
+        assert_eq!(sky.day, Some((DEFAULT_DAY_PERIOD, DEFAULT_DAY_SWING)), "Starts a cycle if there was none");
+        assert_eq!(sky.light(), 0.0, "Midnight is dark");
+        assert_eq!(sky.ambient(TEMP_AMBIENT), TEMP_AMBIENT - DEFAULT_DAY_SWING);
+        sky.set_day_cycle(100, 6);
+        assert_eq!(sky.time_of_day(), 0.25, "A new cycle starts in the morning");
+        assert_eq!(sky.light(), 0.5);
+        sky.set_time_of_day(0.5);
+        assert_eq!(sky.day, Some((100, 6)), "Keeps a running cycle");
+        assert_eq!((sky.time_of_day(), sky.light(), sky.ambient(TEMP_AMBIENT)), (0.5, 1.0, TEMP_AMBIENT + 6));
+        sky.set_time_of_day(-0.25);
+        assert_eq!(sky.time_of_day(), 0.75, "Wraps around");
+    }
+
+    #[test]
+    fn precipitation_only_lands_on_open_sky() {
+        let mut sky = Sky::new();
+        sky.weather = Weather::Snow { intensity: 1.0 };
+        let mut cells = vec![0u8; 8 * CELL_STRIDE];
+        cells[0] = crate::SPECIES_WALL;
+        let mut chunks = ChunkMap::new(8, 1);
+        for _ in 0..1000 { sky.step(&mut cells, 8, 0, None, &mut chunks); }
+        assert_eq!(get_species(&cells, 8, 0, 0), crate::SPECIES_WALL);
+        assert!((1..8).all(|x| get_species(&cells, 8, x, 0) == SPECIES_SNOW));
+    }
+
+    #[test]
+    fn only_exposed_water_evaporates_and_humidity_slows_it() {
+        let mut cells = vec![0u8; 3 * 3 * CELL_STRIDE];
+        for y in 1..3 { crate::set_cell_raw(&mut cells, 3, 0, y, SPECIES_WATER, 0, TEMP_AMBIENT, 0); }
+        crate::set_cell_raw(&mut cells, 3, 2, 2, SPECIES_WATER, 0, TEMP_AMBIENT, 0);
+        let mut chunks = ChunkMap::new(3, 3);
+        for _ in 0..100_000 { evaporate(&mut cells, 3, 3, 1.0, 0, &mut chunks); }
+        assert_eq!(get_species(&cells, 3, 0, 1), SPECIES_WATER, "Saturated air takes nothing");
+        for _ in 0..100_000 {
+            evaporate(&mut cells, 3, 3, 0.0, 0, &mut chunks);
+            if get_species(&cells, 3, 0, 1) != SPECIES_WATER { break; }
+        }
+        assert_eq!(get_species(&cells, 3, 0, 1), SPECIES_STEAM);
+        assert_eq!(get_species(&cells, 3, 0, 2), SPECIES_WATER, "Covered water stays");
+    }
+
+    #[test]
+    fn exposed_mud_dries_to_dirt() {
+        let mut cells = vec![0u8; 3 * 2 * CELL_STRIDE];
+        crate::set_cell_raw(&mut cells, 3, 1, 1, SPECIES_MUD, 0, TEMP_AMBIENT, 0);
+        let mut chunks = ChunkMap::new(3, 2);
+        for _ in 0..100_000 {
+            evaporate(&mut cells, 3, 2, 0.0, 0, &mut chunks);
+            if get_species(&cells, 3, 1, 1) != SPECIES_MUD { break; }
+        }
+        assert_eq!(get_species(&cells, 3, 1, 1), SPECIES_DIRT);
+        assert_eq!(get_species(&cells, 3, 1, 0), SPECIES_STEAM);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::chunks::ChunkMap;
+use crate::{CELL_STRIDE, SPECIES_FIRE, SPECIES_LAVA};
+
+/// Side length in cells of one wind-grid cell.
+pub(crate) const WIND_CELL: usize = 8;
+
+/// Per-tick multiplier on every wind vector: moving air slows down.
+const WIND_DAMPING: f32 = 0.97;
+/// Share of each wind cell replaced by the average of its neighbors per
+/// tick, so gusts spread out instead of stopping at a cell edge.
+const WIND_DIFFUSION: f32 = 0.2;
+/// Upward wind added per tick for each burning cell in a wind cell.
+const FIRE_UPDRAFT: f32 = 0.004;
+/// How many wind cells a fan blows across.
+const FAN_REACH: usize = 4;
+/// Vectors weaker than this on both axes are snapped to zero.
+const WIND_EPSILON: f32 = 0.01;
+
+/// Coarse field of moving air. Like painted forces, each component is the
+/// chance per tick (-1.0..=1.0, sign = direction) that a gas or light
+/// particle drifts one cell along that axis; unlike them the field
+/// spreads out, loses speed on its own and is stirred up by fire.
+pub(crate) struct WindField {
+    cols: usize,
+    rows: usize,
+    vx: Box<[f32]>,
+    vy: Box<[f32]>,
+    scratch: Box<[f32]>,
+}
+
+impl WindField {
+    pub(crate) fn new(width: usize, height: usize) -> WindField {
+        let cols = width.div_ceil(WIND_CELL);
+        let rows = height.div_ceil(WIND_CELL);
+        WindField {
+            cols,
+            rows,
+            vx: vec![0.0; cols * rows].into_boxed_slice(),
+            vy: vec![0.0; cols * rows].into_boxed_slice(),
+            scratch: vec![0.0; cols * rows].into_boxed_slice(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn at(&self, x: usize, y: usize) -> (f32, f32) {
+        let i = (y / WIND_CELL) * self.cols + x / WIND_CELL;
+        (self.vx[i], self.vy[i])
+    }
+
+    /// Overwrites the wind in the wind cell holding (x, y).
+    pub(crate) fn set(&mut self, x: usize, y: usize, dx: f32, dy: f32) {
+        let i = (y / WIND_CELL) * self.cols + x / WIND_CELL;
+        self.vx[i] = dx.clamp(-1.0, 1.0);
+        self.vy[i] = dy.clamp(-1.0, 1.0);
+    }
+
+    /// Blows a cone of air from (x, y) along `dir` (radians, 0 = right,
+    /// y down). `strength` is added at the mouth and fades with distance.
+    pub(crate) fn fan(&mut self, x: usize, y: usize, dir: f32, strength: f32) {
+        let (ux, uy) = (dir.cos(), dir.sin());
+        let (cx, cy) = ((x / WIND_CELL) as f32, (y / WIND_CELL) as f32);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (px, py) = (col as f32 - cx, row as f32 - cy);
+                let along = px * ux + py * uy;
+                let across = (px * uy - py * ux).abs();
+                if along < 0.0 || along > FAN_REACH as f32 || across > along * 0.5 + 0.5 { continue; }
+                let falloff = 1.0 - along / (FAN_REACH as f32 + 1.0);
+                let i = row * self.cols + col;
+                self.vx[i] = (self.vx[i] + ux * strength * falloff).clamp(-1.0, 1.0);
+                self.vy[i] = (self.vy[i] + uy * strength * falloff).clamp(-1.0, 1.0);
+            }
+        }
+    }
+
+    /// One tick of air: fire and lava push air upward, then every vector
+    /// blends with its neighbors and slows down. Wakes the chunks under
+    /// moving air; returns false once the field is completely still and
+    /// nothing is heating it.
+    pub(crate) fn step(&mut self, cells: &[u8], width: usize, chunks: &mut ChunkMap) -> bool {
+        let mut heated = false;
+        for (cell, c) in cells.chunks_exact(CELL_STRIDE).enumerate() {
+            if matches!(c[0], SPECIES_FIRE | SPECIES_LAVA) {
+                heated = true;
+                let i = (cell / width / WIND_CELL) * self.cols + (cell % width) / WIND_CELL;
+                self.vy[i] = (self.vy[i] - FIRE_UPDRAFT).max(-1.0);
+            }
+        }
+        diffuse(&mut self.vx, &mut self.scratch, self.cols, self.rows);
+        diffuse(&mut self.vy, &mut self.scratch, self.cols, self.rows);
+
+        let mut any = false;
+        for i in 0..self.vx.len() {
+            if self.vx[i].abs() < WIND_EPSILON && self.vy[i].abs() < WIND_EPSILON {
+                self.vx[i] = 0.0;
+                self.vy[i] = 0.0;
+            } else {
+                any = true;
+                let x = (i % self.cols) * WIND_CELL + WIND_CELL / 2;
+                let y = (i / self.cols) * WIND_CELL + WIND_CELL / 2;
+                chunks.wake_area(x, y, WIND_CELL / 2);
+            }
+        }
+        any || heated
+    }
+}
+
+/// Blends each value toward the mean of its orthogonal neighbors and
+/// damps it. Edges only average over the neighbors that exist.
+fn diffuse(v: &mut [f32], scratch: &mut [f32], cols: usize, rows: usize) {
+    for row in 0..rows {
+        for col in 0..cols {
+            let mut sum = 0.0;
+            let mut n = 0.0;
+            if col > 0 { sum += v[row * cols + col - 1]; n += 1.0; }
+            if col + 1 < cols { sum += v[row * cols + col + 1]; n += 1.0; }
+            if row > 0 { sum += v[(row - 1) * cols + col]; n += 1.0; }
+            if row + 1 < rows { sum += v[(row + 1) * cols + col]; n += 1.0; }
+            let here = v[row * cols + col];
+            let mean = if n > 0.0 { sum / n } else { here };
+            scratch[row * cols + col] = (here + (mean - here) * WIND_DIFFUSION) * WIND_DAMPING;
+        }
+    }
+    v.copy_from_slice(scratch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_cell_raw;
+
+    #[test]
+    fn gust_spreads_and_dies_down() {
+        let cells = vec![0u8; 64 * 64 * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(64, 64);
+        let mut wind = WindField::new(64, 64);
+        wind.set(32, 32, 1.0, 0.0);
+        wind.step(&cells, 64, &mut chunks);
+        assert!(wind.at(32 + WIND_CELL, 32).0 > 0.0, "Gust should spread to neighbors");
+        assert!(wind.at(32, 32).0 < 1.0);
+        let mut ticks = 0;
+        while wind.step(&cells, 64, &mut chunks) { ticks += 1; }
+        assert!(ticks < 500);
+    }
+
+    #[test]
+    fn fire_makes_an_updraft() {
+        let mut cells = vec![0u8; 32 * 32 * CELL_STRIDE];
+        for x in 8..16 { set_cell_raw(&mut cells, 32, x, 20, SPECIES_FIRE, 50, 150, 0); }
+        let mut chunks = ChunkMap::new(32, 32);
+        let mut wind = WindField::new(32, 32);
+        for _ in 0..10 { wind.step(&cells, 32, &mut chunks); }
+        assert!(wind.at(12, 20).1 < -WIND_EPSILON);
+    }
+
+    #[test]
+    fn a_lone_ember_keeps_still_air_alive() {
+        let mut cells = vec![0u8; 32 * 32 * CELL_STRIDE];
+        let mut chunks = ChunkMap::new(32, 32);
+        let mut wind = WindField::new(32, 32);
+        assert!(!wind.step(&cells, 32, &mut chunks), "Nothing to stir the air");
+        set_cell_raw(&mut cells, 32, 5, 5, SPECIES_FIRE, 50, 150, 0);
+        assert!(wind.step(&cells, 32, &mut chunks), "Too weak to move it, but still burning");
+    }
+
+    #[test]
+    fn fan_blows_ahead_not_behind() {
+        let mut wind = WindField::new(80, 80);
+        wind.fan(40, 40, 0.0, 0.8);
+        assert!(wind.at(40 + 2 * WIND_CELL, 40).0 > 0.0);
+        assert_eq!(wind.at(40 - 2 * WIND_CELL, 40), (0.0, 0.0));
+        assert_eq!(wind.at(40, 40 + 3 * WIND_CELL), (0.0, 0.0), "Cone stays narrow");
+    }
+}
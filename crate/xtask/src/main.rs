@@ -0,0 +1,296 @@
+//! Dev tooling for sand-sim, run as `cargo xtask <command>`.
+//!
+//! `size` builds the wasm module and checks its byte size, `perf` times
+//! each `Testbed` scene natively, and `check` does both. Each result is
+//! compared with `xtask/baselines.txt`; a gate fails when the size grows,
+//! or a scene's score drops, by more than the tolerance. Metrics with no
+//! recorded baseline are reported but never fail. `--record` writes the
+//! measured values as the new baselines.
+//!
+//! Scene scores are relative to the machine: ticks/second divided by the
+//! rate of a fixed calibration loop timed in the same run. A faster or
+//! slower machine moves both together, so baselines recorded on one
+//! machine hold on another to within the perf tolerance.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::hint::black_box;
+use std::process::{Command, ExitCode};
+use std::time::Instant;
+
+use sand_sim::Testbed;
+
+const USAGE: &str = "\
+usage: cargo xtask <size|perf|check> [options]
+
+options:
+  --record                  save the measured values as the new baselines
+  --size-tolerance <pct>    allowed wasm size growth (default 5)
+  --perf-tolerance <pct>    allowed scene score drop (default 15)";
+
+const BASELINES: &str = "baselines.txt";
+const WASM_KEY: &str = "wasm_bytes";
+
+/// Scene size: the web frontend's default canvas.
+const BENCH_WIDTH: usize = 300;
+const BENCH_HEIGHT: usize = 200;
+/// Ticks timed per scene run, from a freshly built world.
+const BENCH_TICKS: u32 = 300;
+/// Runs per scene; the fastest counts, to damp scheduler noise.
+const BENCH_RUNS: u32 = 3;
+/// Xorshift steps in one calibration run.
+const CALIBRATION_STEPS: u32 = 20_000_000;
+/// Scales scene scores to whole numbers worth recording.
+const SCORE_SCALE: f64 = 1000.0;
+
+struct Options {
+    size: bool,
+    perf: bool,
+    record: bool,
+    size_tolerance: f64,
+    perf_tolerance: f64,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let (size, perf) = match args.first().map(String::as_str) {
+        Some("size") => (true, false),
+        Some("perf") => (false, true),
+        Some("check") => (true, true),
+        Some(other) => return Err(format!("unknown command `{other}`")),
+        None => return Err("missing command".into()),
+    };
+    let mut opts = Options { size, perf, record: false, size_tolerance: 5.0, perf_tolerance: 15.0 };
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        let mut percent = || -> Result<f64, String> {
+            let v = rest.next().ok_or_else(|| format!("`{arg}` needs a value"))?;
+            v.parse::<f64>().ok().filter(|p| *p >= 0.0).ok_or_else(|| format!("bad percentage `{v}`"))
+        };
+        match arg.as_str() {
+            "--record" => opts.record = true,
+            "--size-tolerance" => opts.size_tolerance = percent()?,
+            "--perf-tolerance" => opts.perf_tolerance = percent()?,
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    Ok(opts)
+}
+
+/// Reads `key value` lines; blank lines and `#` comments are skipped.
+fn parse_baselines(text: &str) -> BTreeMap<String, f64> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| {
+            let (key, value) = l.split_once(char::is_whitespace)?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+fn format_baselines(values: &BTreeMap<String, f64>) -> String {
+    let mut out = String::from("# Recorded by `cargo xtask <size|perf|check> --record`.\n");
+    for (key, value) in values {
+        out += &format!("{key} {value:.0}\n");
+    }
+    out
+}
+
+/// Which way a metric is allowed to move.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Better {
+    Lower,
+    Higher,
+}
+
+/// Whether `current` is within `tolerance` percent of `baseline` in the
+/// bad direction.
+fn within(current: f64, baseline: f64, better: Better, tolerance: f64) -> bool {
+    match better {
+        Better::Lower => current <= baseline * (1.0 + tolerance / 100.0),
+        Better::Higher => current >= baseline * (1.0 - tolerance / 100.0),
+    }
+}
+
+struct Gate<'a> {
+    baselines: &'a BTreeMap<String, f64>,
+    failed: bool,
+}
+
+impl Gate<'_> {
+    fn report(&mut self, key: &str, current: f64, better: Better, tolerance: f64) {
+        match self.baselines.get(key) {
+            None => println!("{key:<24} {current:>12.0}   (no baseline)"),
+            Some(&baseline) => {
+                let change = (current - baseline) / baseline * 100.0;
+                let ok = within(current, baseline, better, tolerance);
+                self.failed |= !ok;
+                println!(
+                    "{key:<24} {current:>12.0} vs {baseline:>12.0}  {change:>+6.1}%  {}",
+                    if ok { "ok" } else { "FAIL" }
+                );
+            }
+        }
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+/// Builds the release wasm module and returns its size in bytes. This is
+/// cargo's output before wasm-pack runs wasm-opt, so it runs larger than
+/// what ships, but it moves with it.
+fn wasm_size() -> Result<u64, String> {
+    let root = workspace_root();
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let status = Command::new(cargo)
+        .current_dir(&root)
+        .args(["build", "--release", "--lib", "--package", "sand-sim", "--target", "wasm32-unknown-unknown"])
+        .status()
+        .map_err(|e| format!("couldn't run cargo: {e}"))?;
+    if !status.success() {
+        return Err("wasm build failed (is the wasm32-unknown-unknown target installed?)".into());
+    }
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| root.join("target"), PathBuf::from);
+    let wasm = target_dir.join("wasm32-unknown-unknown/release/sand_sim.wasm");
+    std::fs::metadata(&wasm).map(|m| m.len()).map_err(|e| format!("{}: {e}", wasm.display()))
+}
+
+/// Runs per second of `f`: the best of several timed runs.
+fn best_rate(mut f: impl FnMut() -> f64) -> f64 {
+    (0..BENCH_RUNS).map(|_| f()).fold(0.0, f64::max)
+}
+
+/// Calibration runs per second: a fixed integer loop that no change to the
+/// simulation can speed up or slow down, so it measures only the machine.
+fn calibration_rate() -> f64 {
+    best_rate(|| {
+        let start = Instant::now();
+        let mut x = black_box(0x2545_F491_u32);
+        for _ in 0..CALIBRATION_STEPS {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+        }
+        black_box(x);
+        1.0 / start.elapsed().as_secs_f64()
+    })
+}
+
+/// Ticks per second for one scene: the best of several timed runs.
+fn ticks_per_second(bed: Testbed) -> f64 {
+    best_rate(|| {
+        let mut world = bed.build(BENCH_WIDTH, BENCH_HEIGHT);
+        let start = Instant::now();
+        for _ in 0..BENCH_TICKS {
+            world.tick();
+        }
+        BENCH_TICKS as f64 / start.elapsed().as_secs_f64()
+    })
+}
+
+/// A scene's ticks/second relative to the machine's calibration rate.
+fn score(tps: f64, calibration: f64) -> f64 {
+    tps / calibration * SCORE_SCALE
+}
+
+fn run(opts: &Options) -> Result<bool, String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINES);
+    let mut baselines = std::fs::read_to_string(&path).map(|t| parse_baselines(&t)).unwrap_or_default();
+    let mut measured = BTreeMap::new();
+    let mut gate = Gate { baselines: &baselines, failed: false };
+
+    if opts.size {
+        let bytes = wasm_size()? as f64;
+        gate.report(WASM_KEY, bytes, Better::Lower, opts.size_tolerance);
+        measured.insert(WASM_KEY.to_string(), bytes);
+    }
+    if opts.perf {
+        if cfg!(debug_assertions) {
+            eprintln!("warning: xtask built without optimizations; ticks/second will be far off");
+        }
+        let calibration = calibration_rate();
+        for bed in Testbed::ALL {
+            let key = format!("perf.{}", bed.name());
+            let score = score(ticks_per_second(bed), calibration);
+            gate.report(&key, score, Better::Higher, opts.perf_tolerance);
+            measured.insert(key, score);
+        }
+    }
+    let failed = gate.failed;
+
+    if opts.record {
+        baselines.extend(measured);
+        std::fs::write(&path, format_baselines(&baselines)).map_err(|e| format!("{}: {e}", path.display()))?;
+        println!("recorded baselines to {}", path.display());
+        return Ok(true);
+    }
+    Ok(!failed)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let opts = match parse_args(&args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&opts) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => {
+            eprintln!("regression gate failed; rerun with --record if the change is intended");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_commands_and_tolerances() {
+        let opts = parse_args(&args("check --record --perf-tolerance 25")).unwrap();
+        assert!(opts.size && opts.perf && opts.record);
+        assert_eq!(opts.perf_tolerance, 25.0);
+        assert_eq!(opts.size_tolerance, 5.0);
+        assert!(parse_args(&args("perf --size-tolerance")).is_err());
+        assert!(parse_args(&args("perf --size-tolerance -3")).is_err());
+        assert!(parse_args(&args("bench")).is_err());
+    }
+
+    #[test]
+    fn baselines_round_trip() {
+        let text = "# comment\n\nwasm_bytes 120000\nperf.waterfall  850\nbroken\n";
+        let parsed = parse_baselines(text);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed["perf.waterfall"], 850.0);
+        assert_eq!(parse_baselines(&format_baselines(&parsed)), parsed);
+    }
+
+    #[test]
+    fn tolerance_applies_in_the_bad_direction_only() {
+        assert!(within(104.0, 100.0, Better::Lower, 5.0));
+        assert!(!within(106.0, 100.0, Better::Lower, 5.0));
+        assert!(within(50.0, 100.0, Better::Lower, 0.0));
+        assert!(within(90.0, 100.0, Better::Higher, 15.0));
+        assert!(!within(80.0, 100.0, Better::Higher, 15.0));
+    }
+
+    #[test]
+    fn score_follows_the_machine() {
+        assert_eq!(score(600.0, 3.0), score(300.0, 1.5));
+        assert!(score(600.0, 3.0) > score(500.0, 3.0));
+    }
+}
@@ -1,7 +1,7 @@
-import init, { World } from "./wasm/sand_sim";
+import init, { World, cell_stride, offset_species, offset_ra, offset_temp, offset_clock } from "./wasm/sand_sim";
 import { Renderer } from "./renderer";
 import { UI } from "./ui";
-import { Species } from "./types";
+import { Species, type CellLayout } from "./types";
 import { SCENARIOS } from "./scenarios";
 
 const SIM_WIDTH = 300;
@@ -14,7 +14,14 @@ async function main() {
   const memory = wasm.memory;
 
   const canvas = document.getElementById("sand-canvas") as HTMLCanvasElement;
-  const renderer = new Renderer(canvas, SIM_WIDTH, SIM_HEIGHT);
+  const layout: CellLayout = {
+    stride: cell_stride(),
+    species: offset_species(),
+    ra: offset_ra(),
+    temp: offset_temp(),
+    clock: offset_clock(),
+  };
+  const renderer = new Renderer(canvas, SIM_WIDTH, SIM_HEIGHT, layout);
 
   function drawBrush(cx: number, cy: number, species: Species, size: number) {
     const r = Math.max(0, size - 1);
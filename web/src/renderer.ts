@@ -1,4 +1,4 @@
-import { Species, COLORS } from "./types";
+import { Species, COLORS, type CellLayout } from "./types";
 
 const VERTEX_SHADER = `#version 300 es
 in vec2 a_position;
@@ -11,7 +11,17 @@ void main() {
 }
 `;
 
-const FRAGMENT_SHADER = `#version 300 es
+/**
+ * The cell buffer is uploaded as an RGBA8 texture, four bytes to a texel,
+ * so one cell spans `stride / 4` texels. Reads byte `offset` of the cell
+ * at `cell` as a 0..1 float.
+ */
+function cellByte(layout: CellLayout, offset: number): string {
+  const texels = layout.stride / 4;
+  return `texelFetch(u_cells, ivec2(cell.x * ${texels} + ${offset >> 2}, cell.y), 0)[${offset & 3}]`;
+}
+
+const fragmentShader = (layout: CellLayout) => `#version 300 es
 precision mediump float;
 
 in vec2 v_texCoord;
@@ -35,11 +45,12 @@ uniform vec3 u_colorAcid;
 uniform vec3 u_colorWood;
 
 void main() {
-  vec4 cell = texture(u_cells, v_texCoord);
+  ivec2 size = textureSize(u_cells, 0);
+  ivec2 cell = ivec2(v_texCoord * vec2(size.x / ${layout.stride / 4}, size.y));
 
-  int species = int(cell.r * 255.0 + 0.5);
-  float ra = cell.g;
-  float temp = cell.b * 255.0;
+  int species = int(${cellByte(layout, layout.species)} * 255.0 + 0.5);
+  float ra = ${cellByte(layout, layout.ra)};
+  float temp = ${cellByte(layout, layout.temp)} * 255.0;
 
   vec3 color;
 
@@ -159,11 +170,13 @@ export class Renderer {
   private texture: WebGLTexture;
   private simWidth: number;
   private simHeight: number;
+  private layout: CellLayout;
   private destroyed = false;
 
-  constructor(canvas: HTMLCanvasElement, simWidth: number, simHeight: number) {
+  constructor(canvas: HTMLCanvasElement, simWidth: number, simHeight: number, layout: CellLayout) {
     this.simWidth = simWidth;
     this.simHeight = simHeight;
+    this.layout = layout;
 
     const gl = canvas.getContext("webgl2", { antialias: false, alpha: false });
     if (!gl) throw new Error("WebGL2 not supported");
@@ -176,7 +189,7 @@ export class Renderer {
 
     // Compile shaders and link program
     const vs = compileShader(gl, gl.VERTEX_SHADER, VERTEX_SHADER);
-    const fs = compileShader(gl, gl.FRAGMENT_SHADER, FRAGMENT_SHADER);
+    const fs = compileShader(gl, gl.FRAGMENT_SHADER, fragmentShader(layout));
     this.program = createProgram(gl, vs, fs);
     gl.deleteShader(vs);
     gl.deleteShader(fs);
@@ -203,7 +216,7 @@ export class Renderer {
     gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE);
     // Allocate texture storage
     gl.texImage2D(
-      gl.TEXTURE_2D, 0, gl.RGBA8, simWidth, simHeight, 0,
+      gl.TEXTURE_2D, 0, gl.RGBA8, simWidth * layout.stride / 4, simHeight, 0,
       gl.RGBA, gl.UNSIGNED_BYTE, null,
     );
 
@@ -237,14 +250,14 @@ export class Renderer {
     if (this.destroyed) return;
 
     const gl = this.gl;
-    const byteLen = this.simWidth * this.simHeight * 4;
+    const byteLen = this.simWidth * this.simHeight * this.layout.stride;
     const cells = new Uint8Array(memory.buffer, cellsPtr, byteLen);
 
     // Upload cell data as texture
     gl.activeTexture(gl.TEXTURE0);
     gl.bindTexture(gl.TEXTURE_2D, this.texture);
     gl.texSubImage2D(
-      gl.TEXTURE_2D, 0, 0, 0, this.simWidth, this.simHeight,
+      gl.TEXTURE_2D, 0, 0, 0, this.simWidth * this.layout.stride / 4, this.simHeight,
       gl.RGBA, gl.UNSIGNED_BYTE, cells,
     );
 
@@ -8,6 +8,8 @@ import type { Faucet } from "./types";
 let initSync: typeof import("./wasm/sand_sim").initSync;
 let World: typeof import("./wasm/sand_sim").World;
 let wasmMemory: WebAssembly.Memory;
+let cellStride: number;
+let speciesOffset: number;
 
 beforeAll(async () => {
   // Dynamic import so vitest doesn't try to resolve WASM at parse time
@@ -19,6 +21,8 @@ beforeAll(async () => {
   const wasmBytes = readFileSync(wasmPath);
   const exports = initSync(wasmBytes);
   wasmMemory = exports.memory;
+  cellStride = mod.cell_stride();
+  speciesOffset = mod.offset_species();
 });
 
 // --- Constants ---
@@ -65,12 +69,12 @@ function applyFaucets(world: InstanceType<typeof World>, faucets: Faucet[]) {
 
 function readGrid(world: InstanceType<typeof World>): Uint8Array {
   const ptr = world.cells_ptr();
-  const byteLen = W * H * 4;
+  const byteLen = W * H * cellStride;
   return new Uint8Array(wasmMemory.buffer, ptr, byteLen);
 }
 
 function speciesAt(cells: Uint8Array, x: number, y: number): number {
-  return cells[(y * W + x) * 4];
+  return cells[(y * W + x) * cellStride + speciesOffset];
 }
 
 interface RegionCounts {
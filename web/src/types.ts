@@ -15,6 +15,18 @@ export const enum Species {
   Wood = 13,
 }
 
+/**
+ * Byte layout of the simulation's cell buffer, as reported by the wasm
+ * module's `cell_stride()` and `offset_*()` exports.
+ */
+export interface CellLayout {
+  stride: number;
+  species: number;
+  ra: number;
+  temp: number;
+  clock: number;
+}
+
 export interface Faucet {
   x: number;
   y: number;